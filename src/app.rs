@@ -1,31 +1,79 @@
-use anyhow::Result;
+use std::{
+    fmt, fs,
+    path::Path,
+    sync::mpsc::{Receiver, TryRecvError},
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Result};
 use ratatui::{
-    crossterm::event::{poll, read, Event, KeyCode, KeyEvent, KeyEventKind},
+    crossterm::event::{
+        poll, read, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+        MouseEventKind,
+    },
     prelude::*,
-    style::palette::tailwind::SLATE,
-    widgets::{Block, Borders, Clear, Paragraph, Widget, Wrap},
+    widgets::{
+        Block, Borders, Clear, List, ListState, Paragraph, Row, Table, TableState, Widget, Wrap,
+    },
     Terminal,
 };
-use style::palette::tailwind::{AMBER, EMERALD};
 
-use crate::sudoku::{Cell, Difficulty, GameState, Sudoku, MAX_CHECKS, MAX_HINTS};
+use sudoku_core::{
+    code, formats,
+    sudoku::{Board, Cell, Difficulty, GameState, Hint, Sudoku},
+};
 
-const SAVE_FILE: &str = "sudoku.save";
+use crate::{
+    export::{self, ExportFormat},
+    generator::{self, Generator},
+    keymap::{Command, Keymap},
+    saves::{self, Slot},
+    settings::{Setting, Settings},
+    stats::Stats,
+    theme::Theme,
+};
 
 #[derive(Default)]
 pub struct App {
     main_menu: MenuWidget,
     new_game_menu: MenuWidget,
+    open_puzzle: InputWidget,
+    enter_code: InputWidget,
+    puzzle_menu: MenuWidget,
+    puzzles: Vec<Board>,
+    export: InputWidget,
+    save_game: InputWidget,
+    slots: SlotsWidget,
+    rename_slot: InputWidget,
     game: GameWidget,
+    generator: Generator,
+    generating: GeneratingWidget,
+    /// Name of the slot the current game was last saved to or loaded from.
+    slot: Option<String>,
+    toast: ToastWidget,
+    stats: StatsWidget,
+    settings: SettingsWidget,
+    keys: Keymap,
     current_screen: Screen,
     quit: bool,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, Copy)]
 pub enum Screen {
     #[default]
     MainMenu,
     NewGameMenu,
+    OpenPuzzle,
+    EnterCode,
+    PuzzleMenu,
+    Export,
+    SaveGame,
+    LoadGame,
+    RenameSlot,
+    Statistics,
+    Settings,
+    /// Waiting for a puzzle built in the background.
+    Generating,
     Game,
 }
 
@@ -34,18 +82,58 @@ pub struct GameWidget {
     game: Sudoku,
     cursor: (usize, usize),
     show_controls: bool,
+    notes_mode: bool,
+    hint: Option<Hint>,
+    /// Whether the end of the game was reported with [`Action::GameOver`].
+    finished: bool,
+    /// Whether the finished game beat the best time for its difficulty.
+    new_best: bool,
+    /// Selected entry of the history panel, while it's open.
+    history: Option<usize>,
+    keys: Keymap,
+    settings: Settings,
+    theme: Theme,
+    /// Where the board was last drawn, for mapping clicks to cells.
+    board_area: std::cell::Cell<Rect>,
+    tall: std::cell::Cell<bool>,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq)]
 enum Action {
     MoveCursor(isize, isize),
     UpdateCell(u8),
+    CycleCell(isize),
     ClearCell,
+    ToggleNotes,
     Undo,
+    Redo,
+    Bookmark,
+    Rollback,
+    ToggleConflicts,
+    ToggleHistory,
+    JumpTo(usize),
     ClearBoard,
     NewGame(Difficulty),
     SaveGame,
     LoadGame,
+    LoadSlot(usize),
+    DeleteSlot(usize),
+    RenameSlot(usize),
+    OpenPuzzle,
+    EnterCode,
+    SelectPuzzle(usize),
+    ShowStatistics,
+    ShowSettings,
+    /// Sent by the settings screen after changing a setting.
+    SaveSettings,
+    Daily,
+    /// Sent by the generating screen between key presses, to start the game
+    /// once it's ready.
+    Tick,
+    /// Sent once when the game is won or solved, to record it.
+    GameOver,
+    Export,
+    Confirm,
     Pause,
     TogglePause,
     Hint,
@@ -59,10 +147,37 @@ impl App {
     pub fn run(&mut self, mut term: Terminal<impl Backend>) -> Result<()> {
         self.main_menu = MenuWidget::new([
             ("New Game", Action::NewGame(Difficulty::default())),
+            ("Daily", Action::Daily),
             ("Load Game", Action::LoadGame),
+            ("Open puzzle…", Action::OpenPuzzle),
+            ("Enter code…", Action::EnterCode),
+            ("Statistics", Action::ShowStatistics),
+            ("Settings", Action::ShowSettings),
             ("Quit", Action::Quit),
         ]);
 
+        self.open_puzzle = InputWidget::new("Open puzzle (.ss, .sdk, .sdm or one per line)");
+        self.enter_code = InputWidget::new("Enter puzzle code");
+        self.export = InputWidget::new("Export to (.txt, .svg or .pdf)");
+        self.export.value = "sudoku.pdf".to_string();
+        self.save_game = InputWidget::new("Save as (end the name with .json to save as JSON)");
+        self.rename_slot = InputWidget::new("Rename save");
+
+        self.stats.stats = Stats::load().unwrap_or_else(|err| {
+            self.toast.show(format!("{err:#}"));
+            Stats::default()
+        });
+        self.settings.settings = Settings::load().unwrap_or_else(|err| {
+            self.toast.show(format!("{err:#}"));
+            Settings::default()
+        });
+        self.keys = Keymap::load().unwrap_or_else(|err| {
+            self.toast.show(format!("{err:#}"));
+            Keymap::default()
+        });
+        self.game.keys = self.keys.clone();
+        self.slots.keys = self.keys.clone();
+
         self.new_game_menu = MenuWidget::new([
             ("Easy", Action::NewGame(Difficulty::Easy)),
             ("Medium", Action::NewGame(Difficulty::Medium)),
@@ -70,13 +185,18 @@ impl App {
             ("Expert", Action::NewGame(Difficulty::Expert)),
             ("< Back", Action::Quit),
         ]);
+        self.apply_settings();
+        self.generator.start();
 
         while self.is_running() {
             self.draw(&mut term)?;
             let mut current_message = self.handle_events()?;
 
             while let Some(message) = current_message {
-                current_message = self.update(message);
+                current_message = self.update(message).unwrap_or_else(|err| {
+                    self.toast.show(format!("{err:#}"));
+                    None
+                });
             }
         }
         Ok(())
@@ -87,32 +207,277 @@ impl App {
     }
 
     fn draw(&self, term: &mut Terminal<impl Backend>) -> Result<()> {
-        term.draw(|f| match self.current_screen {
+        term.draw(|f| {
+            self.draw_current_screen(f);
+            f.render_widget(&self.toast, f.size());
+        })?;
+        Ok(())
+    }
+
+    fn draw_current_screen(&self, f: &mut Frame) {
+        match self.current_screen {
             Screen::MainMenu => f.render_widget(&self.main_menu, f.size()),
             Screen::NewGameMenu => f.render_widget(&self.new_game_menu, f.size()),
+            Screen::OpenPuzzle => f.render_widget(&self.open_puzzle, f.size()),
+            Screen::EnterCode => f.render_widget(&self.enter_code, f.size()),
+            Screen::PuzzleMenu => f.render_widget(&self.puzzle_menu, f.size()),
+            Screen::Export => f.render_widget(&self.export, f.size()),
+            Screen::SaveGame => f.render_widget(&self.save_game, f.size()),
+            Screen::LoadGame => f.render_widget(&self.slots, f.size()),
+            Screen::RenameSlot => f.render_widget(&self.rename_slot, f.size()),
+            Screen::Statistics => f.render_widget(&self.stats, f.size()),
+            Screen::Settings => f.render_widget(&self.settings, f.size()),
+            Screen::Generating => f.render_widget(&self.generating, f.size()),
             Screen::Game => f.render_widget(&self.game, f.size()),
-        })?;
-        Ok(())
+        }
     }
 
     fn handle_quit(&mut self) -> Option<Action> {
         match self.current_screen {
             Screen::Game => self.current_screen = Screen::MainMenu,
             Screen::NewGameMenu => self.current_screen = Screen::MainMenu,
+            Screen::OpenPuzzle => self.current_screen = Screen::MainMenu,
+            Screen::EnterCode => self.current_screen = Screen::MainMenu,
+            Screen::PuzzleMenu => self.current_screen = Screen::OpenPuzzle,
+            Screen::Export => self.current_screen = Screen::Game,
+            Screen::SaveGame => self.current_screen = Screen::Game,
+            Screen::LoadGame => self.current_screen = Screen::MainMenu,
+            Screen::RenameSlot => self.current_screen = Screen::LoadGame,
+            Screen::Statistics => self.current_screen = Screen::MainMenu,
+            Screen::Settings => self.current_screen = Screen::MainMenu,
+            Screen::Generating => self.current_screen = self.generating.cancel(),
             Screen::MainMenu => self.quit = true,
         }
         None
     }
 
-    fn update_current_screen(&mut self, message: Action) -> Option<Action> {
-        match self.current_screen {
-            Screen::Game => self.game.update(message),
+    /// Passes the settings on to every screen.
+    fn apply_settings(&mut self) {
+        let settings = &self.settings.settings;
+        let theme = settings.theme();
+        self.game.settings = settings.clone();
+        self.game.game.set_limits(settings.limits());
+        self.settings.theme = theme;
+        self.main_menu.theme = theme;
+        self.new_game_menu.theme = theme;
+        self.puzzle_menu.theme = theme;
+        self.open_puzzle.theme = theme;
+        self.enter_code.theme = theme;
+        self.export.theme = theme;
+        self.save_game.theme = theme;
+        self.rename_slot.theme = theme;
+        self.slots.theme = theme;
+        self.stats.theme = theme;
+        self.toast.theme = theme;
+        self.generating.theme = theme;
+        self.game.theme = theme;
+    }
+
+    fn open_puzzles(&mut self) {
+        let puzzles = match formats::read(Path::new(self.open_puzzle.value())) {
+            Ok(puzzles) => puzzles,
+            Err(err) => return self.open_puzzle.set_error(format!("{err:#}")),
+        };
+
+        if let [puzzle] = &puzzles[..] {
+            return self.start_puzzle(puzzle.clone());
+        }
+        let options = (0..puzzles.len())
+            .map(|i| (format!("Puzzle {}", i + 1), Action::SelectPuzzle(i)))
+            .chain([("< Back".to_string(), Action::Quit)]);
+        self.puzzle_menu = MenuWidget::new(options);
+        self.puzzle_menu.theme = self.settings.theme;
+        self.puzzles = puzzles;
+        self.current_screen = Screen::PuzzleMenu;
+    }
+
+    fn new_game(&mut self, difficulty: Difficulty) {
+        let game = self.generator.generate(difficulty);
+        self.wait_for(game, format!("{} puzzle", difficulty.as_str()));
+    }
+
+    /// Starts the game from `game` right away when it's ready, and shows the
+    /// generating screen until it is otherwise.
+    fn wait_for(&mut self, game: Receiver<Sudoku>, label: String) {
+        match game.try_recv() {
+            Ok(game) => self.start_generated(game),
+            Err(_) => {
+                self.generating.label = label;
+                self.generating.game = Some(game);
+                self.generating.back = self.current_screen;
+                self.current_screen = Screen::Generating;
+            }
+        }
+    }
+
+    fn start_generated(&mut self, mut game: Sudoku) {
+        game.reset_clock();
+        self.game.start(game);
+        self.slot = None;
+        self.current_screen = Screen::Game;
+    }
+
+    fn check_generated(&mut self) -> Result<()> {
+        let Some(game) = &self.generating.game else {
+            return Ok(());
+        };
+        match game.try_recv() {
+            Ok(game) => {
+                self.generating.game = None;
+                self.start_generated(game);
+            }
+            Err(TryRecvError::Empty) => self.generating.frame += 1,
+            Err(TryRecvError::Disconnected) => {
+                self.current_screen = self.generating.cancel();
+                bail!("couldn't generate a puzzle");
+            }
+        }
+        Ok(())
+    }
+
+    fn start_puzzle(&mut self, puzzle: Board) {
+        match Sudoku::from_puzzle(puzzle) {
+            Ok(game) => {
+                self.game.start(game);
+                self.slot = None;
+                self.current_screen = Screen::Game;
+            }
+            Err(err) => {
+                self.open_puzzle.set_error(err);
+                self.current_screen = Screen::OpenPuzzle;
+            }
+        }
+    }
+
+    fn enter_code(&mut self) {
+        let game = code::decode(self.enter_code.value()).and_then(Sudoku::from_puzzle);
+        match game {
+            Ok(game) => {
+                self.game.start(game);
+                self.slot = None;
+                self.current_screen = Screen::Game;
+            }
+            Err(err) => self.enter_code.set_error(err),
+        }
+    }
+
+    fn export_game(&mut self) {
+        let path = Path::new(self.export.value());
+        let result = ExportFormat::from_path(path).and_then(|format| {
+            let bytes = export::export(&[*self.game.game.grid()], format);
+            fs::write(path, bytes).map_err(Into::into)
+        });
+        match result {
+            Ok(()) => self.current_screen = Screen::Game,
+            Err(err) => self.export.set_error(err),
+        }
+    }
+
+    /// Goes back to the game whether or not saving worked, so a failed save
+    /// never ends it.
+    fn save_game(&mut self) -> Result<()> {
+        self.current_screen = Screen::Game;
+        let name = self.save_game.value().to_string();
+        saves::save(&saves::dir()?, &name, &self.game.game)?;
+        self.slot = Some(name);
+        Ok(())
+    }
+
+    fn refresh_slots(&mut self) -> Result<()> {
+        self.slots.slots = saves::list(&saves::dir()?)?;
+        self.slots.selected = self
+            .slots
+            .selected
+            .min(self.slots.slots.len().saturating_sub(1));
+        Ok(())
+    }
+
+    fn browse_slots(&mut self) -> Result<()> {
+        self.refresh_slots()?;
+        if self.slots.slots.is_empty() {
+            bail!("No saved game found");
+        }
+        self.current_screen = Screen::LoadGame;
+        Ok(())
+    }
+
+    fn load_slot(&mut self, i: usize) -> Result<()> {
+        let name = &self.slots.slots[i].name;
+        self.game.start(saves::load(&saves::dir()?, name)?);
+        self.slot = Some(name.clone());
+        self.current_screen = Screen::Game;
+        Ok(())
+    }
+
+    fn delete_slot(&mut self, i: usize) -> Result<()> {
+        let name = &self.slots.slots[i].name;
+        saves::delete(&saves::dir()?, name)?;
+        if self.slot.as_ref() == Some(name) {
+            self.slot = None;
+        }
+        self.refresh_slots()
+    }
+
+    fn rename_slot(&mut self) -> Result<()> {
+        let from = &self.slots.slots[self.slots.selected].name;
+        let to = self.rename_slot.value();
+        if let Err(err) = saves::rename(&saves::dir()?, from, to) {
+            self.rename_slot.set_error(format!("{err:#}"));
+            return Ok(());
+        }
+        if self.slot.as_ref() == Some(from) {
+            self.slot = Some(to.to_string());
+        }
+        self.current_screen = Screen::LoadGame;
+        self.refresh_slots()
+    }
+
+    fn update_current_screen(&mut self, message: Action) -> Result<Option<Action>> {
+        let next = match self.current_screen {
+            Screen::Game => match message {
+                Action::Export => {
+                    self.game.update(Action::Pause);
+                    self.current_screen = Screen::Export;
+                    None
+                }
+                Action::NewGame(_) => {
+                    self.game.update(Action::Pause);
+                    self.new_game(self.game.game.difficulty());
+                    None
+                }
+                Action::GameOver => {
+                    self.game.new_best = self.stats.stats.record(&self.game.game)?;
+                    None
+                }
+                Action::SaveGame => {
+                    self.game.update(Action::Pause);
+                    let default_name = || {
+                        let date = chrono::Local::now().format("%Y-%m-%d %H.%M");
+                        format!("{} {date}", self.game.game.difficulty().as_str())
+                    };
+                    self.save_game.value = self.slot.clone().unwrap_or_else(default_name);
+                    self.save_game.error = None;
+                    self.current_screen = Screen::SaveGame;
+                    None
+                }
+                _ => self.game.update(message),
+            },
             Screen::MainMenu => {
                 match message {
-                    Action::NewGame(_) => self.current_screen = Screen::NewGameMenu,
-                    Action::LoadGame => {
-                        self.current_screen = Screen::Game;
-                        self.game.load_game()
+                    Action::NewGame(_) => {
+                        let difficulty = self.settings.settings.difficulty;
+                        self.new_game_menu.select(Action::NewGame(difficulty));
+                        self.current_screen = Screen::NewGameMenu;
+                    }
+                    Action::OpenPuzzle => self.current_screen = Screen::OpenPuzzle,
+                    Action::EnterCode => self.current_screen = Screen::EnterCode,
+                    Action::LoadGame => self.browse_slots()?,
+                    Action::ShowStatistics => self.current_screen = Screen::Statistics,
+                    Action::ShowSettings => self.current_screen = Screen::Settings,
+                    Action::Daily => {
+                        let date = chrono::Local::now().date_naive();
+                        let game = generator::spawn(move || Sudoku::daily(date));
+                        self.wait_for(game, "daily puzzle".to_string());
                     }
                     _ => (),
                 }
@@ -120,27 +485,106 @@ impl App {
             }
             Screen::NewGameMenu => {
                 if let Action::NewGame(difficulty) = message {
-                    self.current_screen = Screen::Game;
-                    self.game.new_game(difficulty);
+                    self.new_game(difficulty);
                 }
                 None
             }
-        }
+            Screen::OpenPuzzle => {
+                if let Action::Confirm = message {
+                    self.open_puzzles();
+                }
+                None
+            }
+            Screen::EnterCode => {
+                if let Action::Confirm = message {
+                    self.enter_code();
+                }
+                None
+            }
+            Screen::PuzzleMenu => {
+                if let Action::SelectPuzzle(i) = message {
+                    self.start_puzzle(self.puzzles[i].clone());
+                }
+                None
+            }
+            Screen::Export => {
+                if let Action::Confirm = message {
+                    self.export_game();
+                }
+                None
+            }
+            Screen::SaveGame => {
+                if let Action::Confirm = message {
+                    self.save_game()?;
+                }
+                None
+            }
+            Screen::LoadGame => {
+                match message {
+                    Action::LoadSlot(i) => self.load_slot(i)?,
+                    Action::DeleteSlot(i) => self.delete_slot(i)?,
+                    Action::RenameSlot(i) => {
+                        self.rename_slot.value = self.slots.slots[i].name.clone();
+                        self.rename_slot.error = None;
+                        self.current_screen = Screen::RenameSlot;
+                    }
+                    _ => (),
+                }
+                None
+            }
+            Screen::RenameSlot => {
+                if let Action::Confirm = message {
+                    self.rename_slot()?;
+                }
+                None
+            }
+            Screen::Statistics => None,
+            Screen::Generating => {
+                if let Action::Tick = message {
+                    self.check_generated()?;
+                }
+                None
+            }
+            Screen::Settings => {
+                if let Action::SaveSettings = message {
+                    self.apply_settings();
+                    self.settings.settings.save()?;
+                }
+                None
+            }
+        };
+        Ok(next)
     }
 
-    fn update(&mut self, message: Action) -> Option<Action> {
+    /// Handles a message, failing when it couldn't be carried out. The error
+    /// is shown in a toast and the app keeps going.
+    fn update(&mut self, message: Action) -> Result<Option<Action>> {
         match message {
-            Action::Quit => self.handle_quit(),
+            Action::Quit => Ok(self.handle_quit()),
             _ => self.update_current_screen(message),
         }
     }
 
     fn handle_events(&mut self) -> Result<Option<Action>> {
+        // Ticks without input, so it keeps checking on the puzzle.
+        if let Screen::Generating = self.current_screen {
+            return self.generating.handle_events(&self.keys);
+        }
         if poll(std::time::Duration::from_millis(100))? {
             return match self.current_screen {
-                Screen::MainMenu => self.main_menu.handle_events(),
+                Screen::MainMenu => self.main_menu.handle_events(&self.keys),
                 Screen::Game => self.game.handle_events(),
-                Screen::NewGameMenu => self.new_game_menu.handle_events(),
+                Screen::NewGameMenu => self.new_game_menu.handle_events(&self.keys),
+                Screen::OpenPuzzle => self.open_puzzle.handle_events(&self.keys),
+                Screen::EnterCode => self.enter_code.handle_events(&self.keys),
+                Screen::PuzzleMenu => self.puzzle_menu.handle_events(&self.keys),
+                Screen::Export => self.export.handle_events(&self.keys),
+                Screen::SaveGame => self.save_game.handle_events(&self.keys),
+                Screen::LoadGame => self.slots.handle_events(),
+                Screen::RenameSlot => self.rename_slot.handle_events(&self.keys),
+                Screen::Statistics => handle_back_events(&self.keys),
+                Screen::Settings => self.settings.handle_events(&self.keys),
+                Screen::Generating => unreachable!("handled above"),
             };
         }
         Ok(None)
@@ -149,34 +593,95 @@ impl App {
 
 impl Widget for &GameWidget {
     fn render(self, area: Rect, buf: &mut Buffer) {
+        let tall = area.height >= GameWidget::TALL_BOARD_HEIGHT + 3;
+        let board_height = if tall {
+            GameWidget::TALL_BOARD_HEIGHT
+        } else {
+            GameWidget::BOARD_HEIGHT
+        };
+
+        let puzzle_code = code::encode(&self.game.puzzle());
+        let code_height = GameWidget::wrapped_height(&puzzle_code);
+        let seed = self.game.seed().map(|seed| seed.to_string());
+        let seed_height = seed.as_deref().map_or(0, GameWidget::wrapped_height);
+        let timer_height = if self.settings.show_timer { 3 } else { 0 };
+        let sidebar_height = timer_height + GameWidget::SIDEBAR_HEIGHT + seed_height + code_height;
+        let main_height = board_height.max(sidebar_height);
+
+        let hint = self.hint.as_ref().filter(|_| self.game.is_running());
+        let bottom_height = if hint.is_some() { 6 } else { 3 };
+
         let [main, controls] = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Max(13), Constraint::Length(3)])
+            .constraints([
+                Constraint::Max(main_height),
+                Constraint::Length(bottom_height),
+            ])
             .flex(layout::Flex::Center)
             .areas(area);
 
-        let [sidebar, game] = Layout::default()
+        let history_width = if self.history.is_some() { 24 } else { 0 };
+        let [sidebar, game, history] = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([Constraint::Length(16), Constraint::Length(42)])
+            .constraints([
+                Constraint::Length(18),
+                Constraint::Length(42),
+                Constraint::Length(history_width),
+            ])
             .flex(layout::Flex::Center)
             .areas(main);
-        self.board().render(game, buf);
+        if let Some(selected) = self.history {
+            let mut state = ListState::default().with_selected(Some(selected));
+            StatefulWidget::render(self.history_panel(), history, buf, &mut state);
+        }
+        let [game] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Max(board_height)])
+            .areas(game);
+        self.board(tall).render(game, buf);
+        self.board_area.set(game);
+        self.tall.set(tall);
 
-        let [timer, diff, hints, checks] = Layout::default()
+        let [timer, diff, rating, hints, checks, bookmarks, seed_area, code] = Layout::default()
             .direction(Direction::Vertical)
-            .constraints(Constraint::from_lengths([3, 3, 3, 3]))
+            .constraints(Constraint::from_lengths([
+                timer_height,
+                3,
+                4,
+                3,
+                3,
+                3,
+                seed_height,
+                code_height,
+            ]))
             .areas(sidebar);
         self.timer().render(timer, buf);
         self.difficulty().render(diff, buf);
+        self.rating().render(rating, buf);
         self.hints().render(hints, buf);
         self.checks().render(checks, buf);
+        self.bookmarks().render(bookmarks, buf);
+        if let Some(seed) = &seed {
+            self.wrapped("Seed", seed).render(seed_area, buf);
+        }
+        self.wrapped("Code", &puzzle_code).render(code, buf);
 
-        let [controls] = Layout::default()
-            .direction(Direction::Horizontal)
-            .flex(layout::Flex::Center)
-            .constraints([Constraint::Fill(1)])
-            .areas(controls);
-        self.controls().render(controls, buf);
+        if let Some(hint) = hint {
+            let [popup] = Layout::default()
+                .direction(Direction::Horizontal)
+                .flex(layout::Flex::Center)
+                .constraints([Constraint::Length(60)])
+                .areas(controls);
+            Clear.render(popup, buf);
+            self.hint_popup(hint).render(popup, buf);
+        } else {
+            let [controls] = Layout::default()
+                .direction(Direction::Horizontal)
+                .flex(layout::Flex::Center)
+                .constraints([Constraint::Fill(1)])
+                .areas(controls);
+            self.controls().render(controls, buf);
+        }
 
         match self.game.state() {
             GameState::Paused => {
@@ -185,7 +690,7 @@ impl Widget for &GameWidget {
                 self.pause_popup().render(area, buf);
             }
             GameState::Won => {
-                let area = centered_rect(90, 30, game);
+                let area = centered_rect(90, 50, game);
                 Clear.render(area, buf);
                 self.won_popup().render(area, buf);
             }
@@ -205,48 +710,108 @@ impl GameWidget {
         self.cursor = (x, y);
     }
 
+    /// Steps the value under the cursor through 0-9, wrapping around.
+    fn cycle_cell(&mut self, step: isize) {
+        let (x, y) = self.cursor;
+        let value = (self.game.at(x, y).value as isize + step).rem_euclid(10);
+        self.game.update_cell(x, y, value as u8);
+    }
+
     fn handle_update_cell(&mut self, value: u8) {
         let (x, y) = self.cursor;
-        self.game.update_cell(x, y, value);
+        match (self.notes_mode, value) {
+            (true, 0) => self.game.clear_notes(x, y),
+            (true, _) => self.game.toggle_note(x, y, value),
+            (false, _) => self.game.update_cell(x, y, value),
+        }
+    }
+
+    /// The first request explains the next deduction, the second one applies it.
+    fn handle_hint(&mut self) {
+        match self.hint.take() {
+            Some(hint) => self.game.apply_hint(&hint),
+            None => self.hint = self.game.hint(),
+        }
     }
 
     fn handle_undo(&mut self) {
-        self.cursor = self.game.undo_last_move().unwrap_or(self.cursor);
+        self.cursor = self.game.undo().unwrap_or(self.cursor);
     }
 
-    fn new_game(&mut self, difficulty: Difficulty) {
-        self.game = Sudoku::generate(difficulty);
-        self.cursor = (0, 0);
+    fn handle_redo(&mut self) {
+        self.cursor = self.game.redo().unwrap_or(self.cursor);
     }
 
-    fn save_game(&mut self) {
-        let bytes = self.game.save().unwrap();
-        std::fs::write(SAVE_FILE, bytes).unwrap();
+    fn toggle_history(&mut self) {
+        self.history = match self.history {
+            Some(_) => None,
+            None => Some(self.game.position()),
+        };
     }
 
-    fn load_game(&mut self) {
-        let bytes = std::fs::read(SAVE_FILE).unwrap();
-        self.game = Sudoku::load(&bytes).unwrap();
+    fn start(&mut self, game: Sudoku) {
+        self.game = game;
+        self.game.set_limits(self.settings.limits());
+        self.cursor = (0, 0);
+        self.notes_mode = false;
+        self.hint = None;
+        self.history = None;
+        self.finished = false;
+        self.new_best = false;
     }
 
     fn update(&mut self, message: Action) -> Option<Action> {
+        let keeps_hint = matches!(
+            message,
+            Action::MoveCursor(..)
+                | Action::ToggleNotes
+                | Action::Pause
+                | Action::TogglePause
+                | Action::Hint
+                | Action::Check
+                | Action::ToggleControls
+                | Action::ToggleHistory
+                | Action::Bookmark
+                | Action::ToggleConflicts
+        );
+        if !keeps_hint {
+            self.hint = None;
+        }
+
         match message {
             Action::MoveCursor(dx, dy) => self.move_cursor(dx, dy),
             Action::UpdateCell(v) => self.handle_update_cell(v),
+            Action::CycleCell(step) => self.cycle_cell(step),
             Action::ClearCell => self.handle_update_cell(0),
+            Action::ToggleNotes => self.notes_mode = !self.notes_mode,
             Action::Undo => self.handle_undo(),
+            Action::Redo => self.handle_redo(),
+            Action::Bookmark => self.game.bookmark(),
+            Action::Rollback => self.game.rollback(),
+            Action::ToggleConflicts => self.game.toggle_conflicts(),
+            Action::ToggleHistory => self.toggle_history(),
+            Action::JumpTo(position) => self.game.jump_to(position),
             Action::ClearBoard => self.game.clear_board(),
             Action::TogglePause => self.game.toggle_pause(),
             Action::Pause => self.game.pause(),
-            Action::SaveGame => self.save_game(),
-            Action::LoadGame => self.load_game(),
-            Action::NewGame(_) => self.new_game(self.game.difficulty()),
-            Action::Hint => self.game.hint(),
+            Action::Hint => self.handle_hint(),
             Action::Solve => self.game.complete(),
             Action::Check => self.game.check(),
             Action::ToggleControls => self.show_controls = !self.show_controls,
             _ => {}
         }
+
+        if !self.finished && matches!(self.game.state(), GameState::Won | GameState::Solved) {
+            self.finished = true;
+            return Some(Action::GameOver);
+        }
+
+        // Keep the history selection on the latest move as the game goes on.
+        if let (Some(selected), false) =
+            (&mut self.history, matches!(message, Action::ToggleHistory))
+        {
+            *selected = self.game.position();
+        }
         None
     }
 
@@ -254,7 +819,8 @@ impl GameWidget {
         if poll(std::time::Duration::from_millis(100))? {
             let msg = match read()? {
                 Event::Key(e) if e.kind == KeyEventKind::Press => self.handle_key_event(e),
-                Event::FocusLost => Some(Action::Pause),
+                Event::Mouse(e) => self.handle_mouse_event(e),
+                Event::FocusLost if self.settings.auto_pause => Some(Action::Pause),
                 _ => None,
             };
             return Ok(msg);
@@ -262,62 +828,178 @@ impl GameWidget {
         Ok(None)
     }
 
-    fn handle_key_event(&mut self, event: KeyEvent) -> Option<Action> {
-        let msg = match event.code {
-            KeyCode::Char('?') => Action::ToggleControls,
-            /* Shift modifier */
-            KeyCode::Char('C') => Action::Solve,
-            KeyCode::Char('N') => Action::NewGame(self.game.difficulty()),
-            KeyCode::Char('X') => Action::ClearBoard,
-            KeyCode::Char('S') => Action::SaveGame,
-            KeyCode::Char('Q') | KeyCode::Esc => Action::Quit,
-            /* */
-            KeyCode::Char('c') => Action::Check,
-            KeyCode::Char('x') => Action::ClearCell,
-            KeyCode::Char('p') => Action::TogglePause,
-            KeyCode::Char('t') => Action::Hint,
-            KeyCode::Char('u') => Action::Undo,
-            KeyCode::Char('h') | KeyCode::Left => Action::MoveCursor(-1, 0),
-            KeyCode::Char('l') | KeyCode::Right => Action::MoveCursor(1, 0),
-            KeyCode::Char('k') | KeyCode::Up => Action::MoveCursor(0, -1),
-            KeyCode::Char('j') | KeyCode::Down => Action::MoveCursor(0, 1),
-            KeyCode::Char(c @ '1'..='9') => {
-                let value = c.to_digit(10).unwrap() as u8;
-                Action::UpdateCell(value)
+    fn handle_mouse_event(&mut self, event: MouseEvent) -> Option<Action> {
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let (x, y) = self.cell_at(event.column, event.row)?;
+                let (cx, cy) = self.cursor;
+                Some(Action::MoveCursor(
+                    x as isize - cx as isize,
+                    y as isize - cy as isize,
+                ))
+            }
+            // Only over the board, so scrolling elsewhere never edits it.
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown
+                if self.cell_at(event.column, event.row).is_none() =>
+            {
+                None
             }
+            MouseEventKind::ScrollUp => Some(Action::CycleCell(1)),
+            MouseEventKind::ScrollDown => Some(Action::CycleCell(-1)),
+            _ => None,
+        }
+    }
+
+    /// Maps a screen position to the board cell drawn there, following the
+    /// layout of [`GameWidget::board`].
+    fn cell_at(&self, column: u16, row: u16) -> Option<(usize, usize)> {
+        let area = self.board_area.get();
+        let inner = Rect::new(
+            area.x + 1,
+            area.y + 1,
+            area.width.saturating_sub(2),
+            area.height.saturating_sub(2),
+        );
+        if !inner.contains(Position::new(column, row)) {
+            return None;
+        }
+
+        // Each cell is 4 columns wide, with a separator after every box, and
+        // the whole row is centered.
+        let line_width = 9 * 4 + 2;
+        let dx = (column - inner.x).checked_sub(inner.width.saturating_sub(line_width) / 2)?;
+        let (bx, offset) = (dx / 13, dx % 13);
+        if bx >= 3 || offset == 12 {
+            return None;
+        }
+        let x = bx * 3 + offset / 4;
+
+        // Boxes are separated by a blank line.
+        let cell_height = if self.tall.get() { 3 } else { 1 };
+        let dy = row - inner.y;
+        let (by, offset) = (dy / (cell_height * 3 + 1), dy % (cell_height * 3 + 1));
+        if by >= 3 || offset == cell_height * 3 {
+            return None;
+        }
+        let y = by * 3 + offset / cell_height;
+
+        Some((x as usize, y as usize))
+    }
+
+    /// While the history panel is open, the up and down keys pick a point
+    /// in it to jump to.
+    fn handle_history_key_event(&mut self, event: KeyEvent) -> Option<Option<Action>> {
+        let selected = self.history.as_mut()?;
+        let msg = match self.keys.command(event)? {
+            Command::MoveUp => {
+                *selected = selected.saturating_sub(1);
+                None
+            }
+            Command::MoveDown => {
+                *selected = (*selected + 1).min(self.game.history().len());
+                None
+            }
+            Command::Select => Some(Action::JumpTo(*selected)),
+            Command::Quit => Some(Action::ToggleHistory),
             _ => return None,
         };
         Some(msg)
     }
+
+    fn handle_key_event(&mut self, event: KeyEvent) -> Option<Action> {
+        if let Some(msg) = self.handle_history_key_event(event) {
+            return msg;
+        }
+        let msg = match self.keys.command(event)? {
+            Command::MoveLeft => Action::MoveCursor(-1, 0),
+            Command::MoveDown => Action::MoveCursor(0, 1),
+            Command::MoveUp => Action::MoveCursor(0, -1),
+            Command::MoveRight => Action::MoveCursor(1, 0),
+            Command::Digit(value) => Action::UpdateCell(value),
+            Command::ClearCell => Action::ClearCell,
+            Command::ToggleNotes => Action::ToggleNotes,
+            Command::Undo => Action::Undo,
+            Command::Redo => Action::Redo,
+            Command::Bookmark => Action::Bookmark,
+            Command::Rollback => Action::Rollback,
+            Command::TogglePause => Action::TogglePause,
+            Command::Hint => Action::Hint,
+            Command::Check => Action::Check,
+            Command::ToggleConflicts => Action::ToggleConflicts,
+            Command::Solve => Action::Solve,
+            Command::NewGame => Action::NewGame(self.game.difficulty()),
+            Command::ClearBoard => Action::ClearBoard,
+            Command::SaveGame => Action::SaveGame,
+            Command::Export => Action::Export,
+            Command::ToggleHistory => Action::ToggleHistory,
+            Command::ToggleControls => Action::ToggleControls,
+            Command::Quit => Action::Quit,
+            Command::Select | Command::Rename | Command::Delete => return None,
+        };
+        Some(msg)
+    }
 }
 
 impl GameWidget {
-    const TEXT_COLOR: Color = SLATE.c400;
-    const WON_COLOR: Color = EMERALD.c300;
-    const PAUSE_COLOR: Color = AMBER.c300;
+    /// Board height with one line per cell.
+    const BOARD_HEIGHT: u16 = 13;
+    /// Board height with each cell drawn as a 3x3 grid of candidates.
+    const TALL_BOARD_HEIGHT: u16 = 31;
+    /// Height of the sidebar without the timer, seed and puzzle code.
+    const SIDEBAR_HEIGHT: u16 = 16;
+
+    /// The first key bound to a command, or nothing when it's unbound.
+    fn key(&self, command: Command) -> String {
+        self.keys
+            .key(command)
+            .map_or_else(String::new, |key| key.to_string())
+    }
 
     fn controls(&self) -> impl Widget {
-        let keys = [
-            ("←↑→↓", "Move"),
-            ("0-9", "Update"),
-            ("u", "Undo"),
-            ("x", "Clear cell"),
-            ("p", "Pause"),
-            ("t", "Hint"),
-            ("c", "Check"),
-            ("^C", "Solve"),
-            ("^N", "New game"),
-            ("^X", "Clear board"),
-            ("^S", "Save game"),
-            ("?", "Show/hide controls"),
-            ("^Q", "Quit"),
+        let moves = [
+            Command::MoveLeft,
+            Command::MoveDown,
+            Command::MoveUp,
+            Command::MoveRight,
+        ]
+        .map(|command| self.key(command))
+        .concat();
+        let digits = format!(
+            "{}-{}",
+            self.key(Command::Digit(1)),
+            self.key(Command::Digit(9))
+        );
+        let commands = [
+            (Command::Undo, "Undo"),
+            (Command::Redo, "Redo"),
+            (Command::Bookmark, "Bookmark"),
+            (Command::Rollback, "Roll back"),
+            (Command::ClearCell, "Clear cell"),
+            (Command::ToggleNotes, "Notes mode"),
+            (Command::TogglePause, "Pause"),
+            (Command::Hint, "Hint"),
+            (Command::Check, "Check"),
+            (Command::ToggleConflicts, "Show conflicts"),
+            (Command::Solve, "Solve"),
+            (Command::NewGame, "New game"),
+            (Command::ClearBoard, "Clear board"),
+            (Command::SaveGame, "Save game"),
+            (Command::Export, "Export"),
+            (Command::ToggleHistory, "History"),
+            (Command::ToggleControls, "Show/hide controls"),
+            (Command::Quit, "Quit"),
         ];
+        let keys = [(moves, "Move"), (digits, "Update")]
+            .into_iter()
+            .chain(commands.map(|(command, desc)| (self.key(command), desc)))
+            .filter(|(key, _)| !key.is_empty());
 
-        let kstyle = Style::default().fg(Color::White).bg(Color::DarkGray);
-        let dstyle = Style::default().fg(Self::TEXT_COLOR).bg(Color::Black);
+        let kstyle = Style::default().fg(self.theme.key).bg(self.theme.key_bg);
+        let dstyle = Style::default()
+            .fg(self.theme.text)
+            .bg(self.theme.legend_bg);
 
         let line: Line = keys
-            .iter()
             .flat_map(|(key, desc)| {
                 let key = Span::styled(format!(" {key} "), kstyle);
                 let desc = Span::styled(format!(" {desc} "), dstyle);
@@ -326,8 +1008,9 @@ impl GameWidget {
             .collect();
 
         if !self.show_controls {
-            return Paragraph::new("press '?' for controls")
-                .fg(Self::TEXT_COLOR)
+            let key = self.key(Command::ToggleControls);
+            return Paragraph::new(format!("press '{key}' for controls"))
+                .fg(self.theme.text)
                 .centered();
         }
 
@@ -336,76 +1019,164 @@ impl GameWidget {
             .left_aligned()
     }
 
+    /// Every move made so far, with the undone ones dimmed, preceded by the
+    /// starting position.
+    fn history_panel(&self) -> List<'static> {
+        let position = self.game.position();
+        let items = std::iter::once("Start".to_string())
+            .chain(self.game.history().iter().map(|mv| mv.to_string()))
+            .enumerate()
+            .map(|(i, text)| {
+                let marker = if i == position { "▶ " } else { "  " };
+                let line = Line::from(format!("{marker}{text}"));
+                if i > position {
+                    line.fg(self.theme.dimmed)
+                } else {
+                    line.fg(self.theme.text)
+                }
+            });
+        List::new(items)
+            .highlight_style(Style::default().reversed())
+            .block(self.theme.block("History").fg(self.theme.text))
+    }
+
     fn pause_popup(&self) -> impl Widget {
-        let text = vec![Line::from("press 'P' to resume").fg(Self::PAUSE_COLOR)];
+        let key = self.key(Command::TogglePause);
+        let text = vec![Line::from(format!("press '{key}' to resume")).fg(self.theme.pause)];
         Paragraph::new(text)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
                     .title("Paused")
-                    .fg(Self::PAUSE_COLOR)
+                    .fg(self.theme.pause)
                     .title_alignment(Alignment::Center),
             )
             .centered()
     }
 
+    fn hint_popup(&self, hint: &Hint) -> impl Widget {
+        let text = vec![
+            Line::from(hint.to_string()),
+            Line::from(format!(
+                "press '{}' again to apply it",
+                self.key(Command::Hint)
+            ))
+            .fg(self.theme.text),
+        ];
+        Paragraph::new(text)
+            .wrap(Wrap { trim: true })
+            .fg(self.theme.hint)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Hint")
+                    .title_alignment(Alignment::Center),
+            )
+    }
+
     fn won_popup(&self) -> impl Widget {
-        let text = vec![Line::from("🎉 You won! 🎉").fg(Self::WON_COLOR)];
+        let mut text = vec![Line::from("🎉 You won! 🎉").fg(self.theme.won)];
+        if self.new_best {
+            text.push(Line::from("New personal best!").fg(self.theme.won));
+        }
+        if self.game.assisted() {
+            text.push(Line::from("with conflict highlighting").fg(self.theme.text));
+        }
         Paragraph::new(text)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .fg(Self::WON_COLOR)
+                    .fg(self.theme.won)
                     .title_alignment(Alignment::Center),
             )
             .centered()
     }
 
     fn timer(&self) -> impl Widget {
-        let text = {
-            let secs = self.game.elapsed().as_secs();
-            let mins = secs / 60;
-            let secs = secs % 60;
-            format!("{:02}:{:02}", mins, secs)
-        };
-        Paragraph::new(text).centered().fg(Self::TEXT_COLOR).block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Time")
-                .title_alignment(Alignment::Center),
-        )
+        Paragraph::new(format_elapsed(self.game.elapsed()))
+            .centered()
+            .fg(self.theme.text)
+            .block(self.theme.block("Time"))
     }
 
     fn difficulty(&self) -> impl Widget {
-        Paragraph::new(self.game.difficulty().as_str())
+        let difficulty = self.game.difficulty().as_str();
+        let text = match self.game.daily_date() {
+            Some(_) => format!("Daily · {difficulty}"),
+            None => difficulty.to_string(),
+        };
+        Paragraph::new(text)
             .centered()
-            .fg(Self::TEXT_COLOR)
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title("Difficulty")
-                    .title_alignment(Alignment::Center),
-            )
+            .fg(self.theme.text)
+            .block(self.theme.block("Difficulty"))
+    }
+
+    fn rating(&self) -> impl Widget {
+        let rating = self.game.rating();
+        let text = vec![
+            Line::from(rating.as_str()),
+            Line::from(format!("Score {}", rating.score)),
+        ];
+        Paragraph::new(text)
+            .centered()
+            .fg(self.theme.text)
+            .block(self.theme.block("Rating"))
     }
 
     fn hints(&self) -> impl Widget {
-        let text = format!("{}/{}", self.game.hints(), MAX_HINTS);
-        Paragraph::new(text).centered().fg(Self::TEXT_COLOR).block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Hints")
-                .title_alignment(Alignment::Center),
-        )
+        let text = format!("{}/{}", self.game.hints(), self.game.limits().hints);
+        Paragraph::new(text)
+            .centered()
+            .fg(self.theme.text)
+            .block(self.theme.block("Hints"))
     }
 
     fn checks(&self) -> impl Widget {
-        let text = format!("{}/{}", self.game.checks(), MAX_CHECKS);
-        Paragraph::new(text).centered().fg(Self::TEXT_COLOR).block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Checks")
-                .title_alignment(Alignment::Center),
-        )
+        let text = format!("{}/{}", self.game.checks(), self.game.limits().checks);
+        Paragraph::new(text)
+            .centered()
+            .fg(self.theme.text)
+            .block(self.theme.block("Checks"))
+    }
+
+    /// A box of `text` wrapped to the sidebar width, such as the code to
+    /// share the puzzle with.
+    fn wrapped<'a>(&self, title: &'a str, text: &'a str) -> impl Widget + 'a {
+        Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .fg(self.theme.text)
+            .block(self.theme.block(title))
+    }
+
+    fn wrapped_height(text: &str) -> u16 {
+        text.len().div_ceil(16) as u16 + 2
+    }
+
+    /// How many bookmarks there are and when the latest one was placed.
+    fn bookmarks(&self) -> impl Widget {
+        let text = match self.game.bookmarks() {
+            [] => "none".to_string(),
+            bookmarks @ [.., last] => format!("{} · move {}", bookmarks.len(), last.position),
+        };
+        Paragraph::new(text)
+            .centered()
+            .fg(self.theme.text)
+            .block(self.theme.block("Bookmarks"))
+    }
+
+    fn hint_bg(&self, x: usize, y: usize) -> Color {
+        let Some(hint) = &self.hint else {
+            return Color::Reset;
+        };
+        if hint.cells().contains(&(x, y)) {
+            self.theme.hint_cell_bg
+        } else if hint.eliminations().iter().any(|&(pos, _)| pos == (x, y)) {
+            self.theme.hint_elimination_bg
+        } else if hint.houses().iter().any(|h| h.cells().contains(&(x, y))) {
+            self.theme.hint_house_bg
+        } else {
+            Color::Reset
+        }
     }
 
     fn cell_style(&self, cell: Cell, x: usize, y: usize) -> Style {
@@ -415,31 +1186,37 @@ impl GameWidget {
         let (cx, cy) = self.cursor;
         let at_cursor = self.game.at(cx, cy).value;
 
-        let highlight_value =
-            |value| self.game.is_running() && value == at_cursor && at_cursor != 0;
+        let highlight_value = |value| {
+            self.settings.highlight_same_digit
+                && self.game.is_running()
+                && value == at_cursor
+                && at_cursor != 0
+        };
 
         let fg_color = match cell {
-            Cell { value: 0, .. } => Color::DarkGray,
-            Cell { value, .. } if highlight_value(value) => Color::LightYellow,
-            Cell { .. } if !cell.writable() => Color::White,
+            Cell { value: 0, .. } => self.theme.dimmed,
+            _ if self.game.shows_conflicts() && self.game.has_conflict(x, y) => self.theme.conflict,
+            Cell { value, .. } if highlight_value(value) => self.theme.highlight,
+            Cell { .. } if !cell.writable() => self.theme.given,
             Cell { .. } if cell.checked() => {
                 if cell.correct() {
-                    Color::Green
+                    self.theme.correct
                 } else {
-                    Color::Red
+                    self.theme.wrong
                 }
             }
-            _ => Color::Blue,
+            _ => self.theme.digit,
         };
 
         let bg_color = match (x == cx, y == cy) {
             _ if !self.game.is_running() => Color::Reset,
-            (true, true) => Color::DarkGray,
-            (true, false) | (false, true) => Color::Black,
+            (true, true) => self.theme.cursor_bg,
+            _ if self.hint.is_some() => self.hint_bg(x, y),
+            (true, false) | (false, true) => self.theme.cursor_line_bg,
             _ => {
                 let (cx, cy) = (cx / 3, cy / 3);
                 if x / 3 == cx && y / 3 == cy {
-                    Color::Black
+                    self.theme.cursor_box_bg
                 } else {
                     Color::Reset
                 }
@@ -449,32 +1226,112 @@ impl GameWidget {
         Style::default().fg(fg_color).bg(bg_color)
     }
 
-    fn board(&self) -> impl Widget {
+    fn cell_spans(&self, cell: Cell, x: usize, y: usize, subrow: Option<u8>) -> Vec<Span<'static>> {
+        let style = self.cell_style(cell, x, y);
+        let show_notes = cell.value == 0 && cell.has_notes() && !self.game.is_paused();
+
+        let (cx, cy) = self.cursor;
+        let at_cursor = self.game.at(cx, cy).value;
+        let note_style = |note| {
+            let highlight = self.settings.highlight_same_digit && self.game.is_running();
+            if highlight && note == at_cursor {
+                style.fg(self.theme.highlight)
+            } else {
+                style.fg(self.theme.dimmed)
+            }
+        };
+
+        if let (Some(subrow), true) = (subrow, show_notes) {
+            let mut spans: Vec<Span> = (1..=3)
+                .map(|i| subrow * 3 + i)
+                .map(|note| {
+                    if !cell.has_note(note) {
+                        return Span::styled(" ", style);
+                    }
+                    Span::styled(((note + b'0') as char).to_string(), note_style(note))
+                })
+                .collect();
+            spans.push(Span::styled(" ", style));
+            return spans;
+        }
+
+        // Without room for the 3x3 grid, up to three notes as superscripts.
+        if subrow.is_none() && show_notes {
+            const SUPERSCRIPTS: [&str; 10] = ["⁰", "¹", "²", "³", "⁴", "⁵", "⁶", "⁷", "⁸", "⁹"];
+            let notes: Vec<u8> = (1..=9).filter(|&note| cell.has_note(note)).collect();
+            let shown = if notes.len() > 3 { 2 } else { notes.len() };
+            let mut spans: Vec<Span> = notes[..shown]
+                .iter()
+                .map(|&note| Span::styled(SUPERSCRIPTS[note as usize], note_style(note)))
+                .collect();
+            if notes.len() > 3 {
+                spans.push(Span::styled("…", style.fg(self.theme.dimmed)));
+            }
+            if spans.len() == 1 {
+                spans.insert(0, Span::styled(" ", style));
+            }
+            while spans.len() < 4 {
+                spans.push(Span::styled(" ", style));
+            }
+            return spans;
+        }
+
+        if subrow.is_some_and(|subrow| subrow != 1) {
+            return vec![Span::styled("    ", style)];
+        }
+
+        let num = cell.value;
+        let ch = if num == 0 { b'.' } else { num + b'0' } as char;
+        let ch = format!("{:^3} ", if self.game.is_paused() { '*' } else { ch });
+        vec![Span::styled(ch, style)]
+    }
+
+    fn board(&self, tall: bool) -> impl Widget {
+        let subrows: &[Option<u8>] = if tall {
+            &[Some(0), Some(1), Some(2)]
+        } else {
+            &[None]
+        };
+
         let mut content = Text::default();
         for (y, row) in self.game.grid().iter().enumerate() {
             if y % 3 == 0 && y != 0 {
                 content.push_line("\n");
             }
-            for (x, &cell) in row.iter().enumerate() {
-                if x % 3 == 0 && x != 0 {
-                    content.push_span(("│").fg(Self::TEXT_COLOR));
+            for &subrow in subrows {
+                for (x, &cell) in row.iter().enumerate() {
+                    if x % 3 == 0 && x != 0 {
+                        content.push_span(("│").fg(self.theme.border));
+                    }
+                    for span in self.cell_spans(cell, x, y, subrow) {
+                        content.push_span(span);
+                    }
                 }
-                let num = cell.value;
-                let ch = if num == 0 { b'.' } else { num + b'0' } as char;
-                let ch = format!("{:^3} ", if self.game.is_paused() { '*' } else { ch });
-                let style = self.cell_style(cell, x, y);
-                content.push_span(Span::styled(ch, style));
+                content.push_line("\n");
             }
-            content.push_line("\n");
         }
 
+        let modes: Vec<_> = [
+            (self.notes_mode, "Notes"),
+            (self.game.shows_conflicts(), "Conflicts"),
+        ]
+        .into_iter()
+        .filter_map(|(on, mode)| on.then_some(mode))
+        .collect();
+        let block = self.theme.block(modes.join(" · "));
+
         Paragraph::new(content)
             .centered()
-            .block(Block::default().borders(Borders::ALL))
-            .style(Style::default().fg(Self::TEXT_COLOR))
+            .block(block)
+            .style(Style::default().fg(self.theme.text))
     }
 }
 
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::vertical([
         Constraint::Percentage((100 - percent_y) / 2),
@@ -491,15 +1348,70 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     .split(popup_layout[1])[1]
 }
 
+/// Where the rows of a list were last drawn and how far it was scrolled,
+/// for mapping clicks to rows.
+#[derive(Default)]
+struct ListArea {
+    rows: std::cell::Cell<Rect>,
+    offset: std::cell::Cell<usize>,
+}
+
+impl ListArea {
+    fn set(&self, rows: Rect, offset: usize) {
+        self.rows.set(rows);
+        self.offset.set(offset);
+    }
+
+    /// Moves `selected` through a list of `len` rows as the wheel scrolls
+    /// or a row is clicked, returning the row clicked.
+    fn handle_mouse_event(
+        &self,
+        event: MouseEvent,
+        len: usize,
+        selected: &mut usize,
+    ) -> Option<usize> {
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let rows = self.rows.get();
+                if !rows.contains(Position::new(event.column, event.row)) {
+                    return None;
+                }
+                let index = (event.row - rows.y) as usize + self.offset.get();
+                if index >= len {
+                    return None;
+                }
+                *selected = index;
+                Some(index)
+            }
+            MouseEventKind::ScrollDown => {
+                *selected = (*selected + 1).min(len.saturating_sub(1));
+                None
+            }
+            MouseEventKind::ScrollUp => {
+                *selected = selected.saturating_sub(1);
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
 #[derive(Default)]
 struct MenuWidget {
     options: Vec<(String, Action)>,
     selected: usize,
+    list: ListArea,
+    theme: Theme,
 }
 
 impl Widget for &MenuWidget {
     fn render(self, area: Rect, buf: &mut Buffer) {
         let centered = centered_rect(30, 30, area);
+        // Keep the selected option in view when they don't all fit.
+        let height = centered.height.saturating_sub(2) as usize;
+        let scroll = (self.selected + 1).saturating_sub(height);
+        let block = self.theme.block("Sudoku").fg(self.theme.text);
+        self.list.set(block.inner(centered), scroll);
         let text = self
             .options
             .iter()
@@ -508,7 +1420,7 @@ impl Widget for &MenuWidget {
                 let style = if i == self.selected {
                     Style::default().reversed()
                 } else {
-                    Style::default().fg(SLATE.c400)
+                    Style::default().fg(self.theme.text)
                 };
                 Line::styled(option, style).centered()
             })
@@ -516,13 +1428,8 @@ impl Widget for &MenuWidget {
 
         Paragraph::new(text)
             .wrap(Wrap { trim: true })
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title("Sudoku")
-                    .title_alignment(Alignment::Center)
-                    .fg(SLATE.c400),
-            )
+            .scroll((scroll as u16, 0))
+            .block(block)
             .render(centered, buf);
     }
 }
@@ -535,20 +1442,39 @@ impl MenuWidget {
     {
         Self {
             options: options.into_iter().map(|(s, m)| (s.into(), m)).collect(),
-            selected: 0,
+            ..Default::default()
         }
     }
 
-    fn handle_key_event(&mut self, event: KeyEvent) -> Option<Action> {
-        match event.code {
-            KeyCode::Esc => return Some(Action::Quit),
-            KeyCode::Char('j') | KeyCode::Down => {
+    /// Moves the selection to the option sending `action`, if there is one.
+    fn select(&mut self, action: Action) {
+        if let Some(i) = self
+            .options
+            .iter()
+            .position(|(_, option)| *option == action)
+        {
+            self.selected = i;
+        }
+    }
+
+    fn handle_mouse_event(&mut self, event: MouseEvent) -> Option<Action> {
+        let index = self
+            .list
+            .handle_mouse_event(event, self.options.len(), &mut self.selected)?;
+        Some(self.options[index].1)
+    }
+
+    fn handle_key_event(&mut self, event: KeyEvent, keys: &Keymap) -> Option<Action> {
+        match keys.command(event)? {
+            Command::Quit => return Some(Action::Quit),
+            _ if self.options.is_empty() => {}
+            Command::MoveDown => {
                 self.selected = (self.selected + 1) % self.options.len();
             }
-            KeyCode::Char('k') | KeyCode::Up => {
+            Command::MoveUp => {
                 self.selected = self.selected.saturating_sub(1) % self.options.len();
             }
-            KeyCode::Enter => {
+            Command::Select => {
                 let &(_, msg) = &self.options[self.selected];
                 return Some(msg);
             }
@@ -557,10 +1483,134 @@ impl MenuWidget {
         None
     }
 
+    fn handle_events(&mut self, keys: &Keymap) -> Result<Option<Action>> {
+        if poll(std::time::Duration::from_millis(100))? {
+            let msg = match read()? {
+                Event::Key(e) if e.kind == KeyEventKind::Press => self.handle_key_event(e, keys),
+                Event::Mouse(e) => self.handle_mouse_event(e),
+                _ => None,
+            };
+            return Ok(msg);
+        }
+        Ok(None)
+    }
+}
+
+/// Lists the saved games, with keys to load, rename and delete them.
+#[derive(Default)]
+struct SlotsWidget {
+    slots: Vec<Slot>,
+    selected: usize,
+    /// Set by the first press of `d`, the second one deletes the slot.
+    confirm_delete: bool,
+    list: ListArea,
+    keys: Keymap,
+    theme: Theme,
+}
+
+impl Widget for &SlotsWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let centered = centered_rect(80, 60, area);
+        let block = self.theme.block("Load Game").fg(self.theme.text);
+        let [list, help] = Layout::vertical([Constraint::Fill(1), Constraint::Length(1)])
+            .areas(block.inner(centered));
+        block.render(centered, buf);
+
+        let key = |command| {
+            self.keys
+                .key(command)
+                .map_or_else(String::new, |key| key.to_string())
+        };
+        let help_text = match self.slots.get(self.selected) {
+            None => format!("No saved games. {}: back", key(Command::Quit)),
+            Some(slot) if self.confirm_delete => format!(
+                "Press {} again to delete {}",
+                key(Command::Delete),
+                slot.name
+            ),
+            Some(_) => format!(
+                "{}: load  {}: rename  {}: delete  {}: back",
+                key(Command::Select),
+                key(Command::Rename),
+                key(Command::Delete),
+                key(Command::Quit)
+            ),
+        };
+        Paragraph::new(help_text).centered().render(help, buf);
+
+        let rows = self.slots.iter().map(|slot| {
+            let modified = chrono::DateTime::<chrono::Local>::from(slot.modified);
+            let modified = modified.format("%Y-%m-%d %H:%M").to_string();
+            match &slot.summary {
+                Some(summary) => Row::new([
+                    slot.name.clone(),
+                    summary.difficulty.as_str().to_string(),
+                    format_elapsed(summary.elapsed),
+                    format!("{}%", summary.filled),
+                    modified,
+                ]),
+                None => Row::new([slot.name.clone(), "Unreadable".to_string()])
+                    .fg(self.theme.error)
+                    .add_modifier(Modifier::DIM),
+            }
+        });
+        let widths = [
+            Constraint::Fill(1),
+            Constraint::Length(10),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(16),
+        ];
+        let header = Row::new(["Name", "Difficulty", "Time", "Filled", "Last played"]).bold();
+        let table = Table::new(rows, widths)
+            .header(header)
+            .highlight_style(Style::default().reversed());
+        let mut state = TableState::default().with_selected(Some(self.selected));
+        StatefulWidget::render(table, list, buf, &mut state);
+        let [_, rows] = Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(list);
+        self.list.set(rows, state.offset());
+    }
+}
+
+impl SlotsWidget {
+    fn handle_key_event(&mut self, event: KeyEvent) -> Option<Action> {
+        let confirm_delete = std::mem::take(&mut self.confirm_delete);
+        let command = self.keys.command(event)?;
+        if command == Command::Quit {
+            return Some(Action::Quit);
+        }
+        if self.slots.is_empty() {
+            return None;
+        }
+        match command {
+            Command::MoveDown => {
+                self.selected = (self.selected + 1).min(self.slots.len() - 1);
+            }
+            Command::MoveUp => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            Command::Select => return Some(Action::LoadSlot(self.selected)),
+            Command::Rename => return Some(Action::RenameSlot(self.selected)),
+            Command::Delete if confirm_delete => return Some(Action::DeleteSlot(self.selected)),
+            Command::Delete => self.confirm_delete = true,
+            _ => {}
+        }
+        None
+    }
+
+    fn handle_mouse_event(&mut self, event: MouseEvent) -> Option<Action> {
+        self.confirm_delete = false;
+        let index = self
+            .list
+            .handle_mouse_event(event, self.slots.len(), &mut self.selected)?;
+        Some(Action::LoadSlot(index))
+    }
+
     fn handle_events(&mut self) -> Result<Option<Action>> {
         if poll(std::time::Duration::from_millis(100))? {
             let msg = match read()? {
                 Event::Key(e) if e.kind == KeyEventKind::Press => self.handle_key_event(e),
+                Event::Mouse(e) => self.handle_mouse_event(e),
                 _ => None,
             };
             return Ok(msg);
@@ -568,3 +1618,457 @@ impl MenuWidget {
         Ok(None)
     }
 }
+
+/// Wins and times per difficulty.
+#[derive(Default)]
+struct StatsWidget {
+    stats: Stats,
+    theme: Theme,
+}
+
+impl Widget for &StatsWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let centered = centered_rect(80, 60, area);
+        let block = self.theme.block("Statistics").fg(self.theme.text);
+        let [table, streak, daily, help] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(block.inner(centered));
+        block.render(centered, buf);
+
+        let time = |time: Option<Duration>| time.map_or("-".to_string(), format_elapsed);
+        let rows = Difficulty::ALL.map(|difficulty| {
+            let summary = self.stats.summary(difficulty);
+            Row::new([
+                difficulty.as_str().to_string(),
+                summary.played.to_string(),
+                summary.won.to_string(),
+                time(summary.best),
+                time(summary.average),
+                time(summary.assisted_average),
+            ])
+        });
+        let widths = [
+            Constraint::Fill(1),
+            Constraint::Length(6),
+            Constraint::Length(4),
+            Constraint::Length(6),
+            Constraint::Length(8),
+            Constraint::Length(13),
+        ];
+        let header = [
+            "Difficulty",
+            "Played",
+            "Won",
+            "Best",
+            "Average",
+            "With assists",
+        ];
+        let summaries = Table::new(rows, widths).header(Row::new(header).bold());
+        Widget::render(summaries, table, buf);
+
+        Paragraph::new(format!("Current streak: {}", self.stats.streak()))
+            .centered()
+            .render(streak, buf);
+        let daily_text = format!(
+            "Daily puzzles solved: {}, streak: {} days",
+            self.stats.daily_won(),
+            self.stats.daily_streak()
+        );
+        Paragraph::new(daily_text).centered().render(daily, buf);
+        Paragraph::new("Esc: back").centered().render(help, buf);
+    }
+}
+
+/// Preferences, changed in place and saved on every change.
+#[derive(Default)]
+struct SettingsWidget {
+    settings: Settings,
+    selected: usize,
+    list: ListArea,
+    theme: Theme,
+}
+
+impl Widget for &SettingsWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let centered = centered_rect(60, 50, area);
+        let block = self.theme.block("Settings").fg(self.theme.text);
+        let [list, help] = Layout::vertical([Constraint::Fill(1), Constraint::Length(1)])
+            .areas(block.inner(centered));
+        block.render(centered, buf);
+
+        let rows = Setting::ALL.map(|setting| {
+            Row::new([
+                setting.as_str().to_string(),
+                format!("< {} >", setting.value(&self.settings)),
+            ])
+        });
+        let table = Table::new(rows, [Constraint::Fill(1), Constraint::Fill(1)])
+            .highlight_style(Style::default().reversed());
+        let mut state = TableState::default().with_selected(Some(self.selected));
+        StatefulWidget::render(table, list, buf, &mut state);
+        self.list.set(list, state.offset());
+        Paragraph::new("←→: change  Esc: back")
+            .centered()
+            .render(help, buf);
+    }
+}
+
+impl SettingsWidget {
+    fn handle_key_event(&mut self, event: KeyEvent, keys: &Keymap) -> Option<Action> {
+        let step = match keys.command(event)? {
+            Command::Quit => return Some(Action::Quit),
+            Command::MoveDown => {
+                self.selected = (self.selected + 1).min(Setting::ALL.len() - 1);
+                return None;
+            }
+            Command::MoveUp => {
+                self.selected = self.selected.saturating_sub(1);
+                return None;
+            }
+            Command::MoveLeft => -1,
+            Command::MoveRight | Command::Select => 1,
+            _ => return None,
+        };
+        Setting::ALL[self.selected].change(&mut self.settings, step);
+        Some(Action::SaveSettings)
+    }
+
+    /// Clicking a setting changes it as Enter would.
+    fn handle_mouse_event(&mut self, event: MouseEvent) -> Option<Action> {
+        let index = self
+            .list
+            .handle_mouse_event(event, Setting::ALL.len(), &mut self.selected)?;
+        Setting::ALL[index].change(&mut self.settings, 1);
+        Some(Action::SaveSettings)
+    }
+
+    fn handle_events(&mut self, keys: &Keymap) -> Result<Option<Action>> {
+        if poll(std::time::Duration::from_millis(100))? {
+            let msg = match read()? {
+                Event::Key(e) if e.kind == KeyEventKind::Press => self.handle_key_event(e, keys),
+                Event::Mouse(e) => self.handle_mouse_event(e),
+                _ => None,
+            };
+            return Ok(msg);
+        }
+        Ok(None)
+    }
+}
+
+/// A spinner shown while a puzzle is built in the background.
+#[derive(Default)]
+struct GeneratingWidget {
+    /// What's being built, such as "Expert puzzle".
+    label: String,
+    /// Receives the game once it's built. Dropped to cancel.
+    game: Option<Receiver<Sudoku>>,
+    /// Number of ticks waited, which turns the spinner.
+    frame: usize,
+    /// Screen to go back to when cancelled.
+    back: Screen,
+    theme: Theme,
+}
+
+impl Widget for &GeneratingWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+        let centered = centered_rect(60, 30, area);
+        let spinner = SPINNER[self.frame % SPINNER.len()];
+        let text = vec![
+            Line::default(),
+            Line::from(format!("{spinner} Generating a new {}…", self.label)),
+            Line::default(),
+            Line::from("Esc: cancel").fg(self.theme.dimmed),
+        ];
+        Paragraph::new(text)
+            .centered()
+            .block(self.theme.block("New Game").fg(self.theme.text))
+            .render(centered, buf);
+    }
+}
+
+impl GeneratingWidget {
+    /// How long to wait for a key before checking on the puzzle again.
+    const TICK: Duration = Duration::from_millis(80);
+
+    /// Stops waiting for the puzzle, returning the screen to go back to.
+    fn cancel(&mut self) -> Screen {
+        self.game = None;
+        self.back
+    }
+
+    fn handle_events(&self, keys: &Keymap) -> Result<Option<Action>> {
+        if poll(Self::TICK)? {
+            if let Event::Key(e) = read()? {
+                if e.kind == KeyEventKind::Press && keys.command(e) == Some(Command::Quit) {
+                    return Ok(Some(Action::Quit));
+                }
+            }
+        }
+        Ok(Some(Action::Tick))
+    }
+}
+
+/// Events for screens that only show something: the keys to quit or
+/// select go back.
+fn handle_back_events(keys: &Keymap) -> Result<Option<Action>> {
+    if poll(std::time::Duration::from_millis(100))? {
+        let msg = match read()? {
+            Event::Key(e) if e.kind == KeyEventKind::Press => match keys.command(e) {
+                Some(Command::Quit | Command::Select) => Some(Action::Quit),
+                _ => None,
+            },
+            _ => None,
+        };
+        return Ok(msg);
+    }
+    Ok(None)
+}
+
+/// A message shown at the bottom of any screen for a few seconds.
+#[derive(Default)]
+struct ToastWidget {
+    message: Option<(String, Instant)>,
+    theme: Theme,
+}
+
+impl Widget for &ToastWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let Some((message, _)) = self.message.as_ref().filter(|_| self.is_visible()) else {
+            return;
+        };
+        let [_, bottom] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(4)]).areas(area);
+        let [popup] = Layout::horizontal([Constraint::Max(60)])
+            .flex(layout::Flex::Center)
+            .areas(bottom);
+        Clear.render(popup, buf);
+        Paragraph::new(message.as_str())
+            .wrap(Wrap { trim: true })
+            .fg(self.theme.error)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Error")
+                    .title_alignment(Alignment::Center),
+            )
+            .render(popup, buf);
+    }
+}
+
+impl ToastWidget {
+    const DURATION: Duration = Duration::from_secs(4);
+
+    fn show(&mut self, message: impl Into<String>) {
+        self.message = Some((message.into(), Instant::now()));
+    }
+
+    fn is_visible(&self) -> bool {
+        self.message
+            .as_ref()
+            .is_some_and(|(_, shown)| shown.elapsed() < Self::DURATION)
+    }
+}
+
+#[derive(Default)]
+struct InputWidget {
+    title: String,
+    value: String,
+    error: Option<String>,
+    theme: Theme,
+}
+
+impl Widget for &InputWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let centered = centered_rect(60, 30, area);
+        let mut text = vec![Line::from(format!("> {}█", self.value)).fg(self.theme.input)];
+        if let Some(error) = &self.error {
+            text.push(Line::default());
+            text.push(Line::from(error.as_str()).fg(self.theme.error));
+        }
+
+        Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .block(self.theme.block(self.title.as_str()).fg(self.theme.text))
+            .render(centered, buf);
+    }
+}
+
+impl InputWidget {
+    fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            ..Default::default()
+        }
+    }
+
+    fn value(&self) -> &str {
+        self.value.trim()
+    }
+
+    fn set_error(&mut self, error: impl fmt::Display) {
+        self.error = Some(error.to_string());
+    }
+
+    fn handle_key_event(&mut self, event: KeyEvent, keys: &Keymap) -> Option<Action> {
+        let typing = !event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        match (event.code, keys.command(event)) {
+            // Typed characters go in the text even when bound to a command.
+            (KeyCode::Char(c), _) if typing => self.value.push(c),
+            (KeyCode::Backspace, _) => {
+                self.value.pop();
+            }
+            (_, Some(Command::Quit)) => return Some(Action::Quit),
+            (_, Some(Command::Select)) => return Some(Action::Confirm),
+            _ => return None,
+        }
+        self.error = None;
+        None
+    }
+
+    fn handle_events(&mut self, keys: &Keymap) -> Result<Option<Action>> {
+        if poll(std::time::Duration::from_millis(100))? {
+            let msg = match read()? {
+                Event::Key(e) if e.kind == KeyEventKind::Press => self.handle_key_event(e, keys),
+                _ => None,
+            };
+            return Ok(msg);
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keymap::Preset;
+
+    #[test]
+    fn empty_menu_ignores_input() {
+        let mut menu = MenuWidget::default();
+        let keys = Keymap::default();
+        for code in [KeyCode::Down, KeyCode::Up, KeyCode::Enter] {
+            let event = KeyEvent::new(code, KeyModifiers::NONE);
+            assert!(menu.handle_key_event(event, &keys).is_none());
+        }
+        for kind in [MouseEventKind::ScrollDown, MouseEventKind::ScrollUp] {
+            assert!(menu.handle_mouse_event(mouse(kind, 0, 0)).is_none());
+        }
+        assert_eq!(menu.selected, 0);
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn saves_list_follows_the_keymap() {
+        let slot = |name: &str| Slot {
+            name: name.to_string(),
+            modified: std::time::SystemTime::UNIX_EPOCH,
+            summary: None,
+        };
+        let mut slots = SlotsWidget {
+            slots: vec![slot("a"), slot("b")],
+            keys: Keymap::preset(Preset::Vim),
+            ..Default::default()
+        };
+        // Vim leaves the arrow keys unbound.
+        assert!(slots.handle_key_event(key(KeyCode::Down)).is_none());
+        assert_eq!(slots.selected, 0);
+        assert!(slots.handle_key_event(key(KeyCode::Char('j'))).is_none());
+        assert_eq!(slots.selected, 1);
+        assert!(matches!(
+            slots.handle_key_event(key(KeyCode::Char('R'))),
+            Some(Action::RenameSlot(1))
+        ));
+        assert!(slots.handle_key_event(key(KeyCode::Char('D'))).is_none());
+        assert!(matches!(
+            slots.handle_key_event(key(KeyCode::Char('D'))),
+            Some(Action::DeleteSlot(1))
+        ));
+        assert!(matches!(
+            slots.handle_key_event(key(KeyCode::Esc)),
+            Some(Action::Quit)
+        ));
+    }
+
+    #[test]
+    fn inputs_type_bound_characters() {
+        let keys = Keymap::default();
+        let mut input = InputWidget::default();
+        assert!(input
+            .handle_key_event(key(KeyCode::Char('Q')), &keys)
+            .is_none());
+        assert_eq!(input.value(), "Q");
+        assert!(matches!(
+            input.handle_key_event(key(KeyCode::Enter), &keys),
+            Some(Action::Confirm)
+        ));
+        assert!(matches!(
+            input.handle_key_event(key(KeyCode::Esc), &keys),
+            Some(Action::Quit)
+        ));
+    }
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn scrolls_only_over_the_board() {
+        let mut game = GameWidget::default();
+        game.start(Sudoku::generate_seeded(Difficulty::Easy, 1));
+        let area = Rect::new(0, 0, 100, 40);
+        game.render(area, &mut Buffer::empty(area));
+        let board = game.board_area.get();
+
+        let (x, y) = (board.x + board.width / 2, board.y + board.height / 2);
+        let on_board = game.handle_mouse_event(mouse(MouseEventKind::ScrollUp, x, y));
+        assert!(matches!(on_board, Some(Action::CycleCell(1))));
+        for (x, y) in [(board.x - 1, y), (x, board.bottom()), (0, 0)] {
+            assert!(game
+                .handle_mouse_event(mouse(MouseEventKind::ScrollUp, x, y))
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn clicks_and_scrolls_through_settings() {
+        let mut settings = SettingsWidget::default();
+        let area = Rect::new(0, 0, 80, 40);
+        settings.render(area, &mut Buffer::empty(area));
+        let rows = settings.list.rows.get();
+
+        let scroll = mouse(MouseEventKind::ScrollDown, 0, 0);
+        assert!(settings.handle_mouse_event(scroll).is_none());
+        assert_eq!(settings.selected, 1);
+
+        let before = Setting::ALL[2].value(&settings.settings);
+        let click = mouse(MouseEventKind::Down(MouseButton::Left), rows.x, rows.y + 2);
+        assert!(matches!(
+            settings.handle_mouse_event(click),
+            Some(Action::SaveSettings)
+        ));
+        assert_eq!(settings.selected, 2);
+        assert_ne!(Setting::ALL[2].value(&settings.settings), before);
+
+        let below = mouse(
+            MouseEventKind::Down(MouseButton::Left),
+            rows.x,
+            rows.bottom(),
+        );
+        assert!(settings.handle_mouse_event(below).is_none());
+    }
+}
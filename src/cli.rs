@@ -0,0 +1,186 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use sudoku_core::{
+    formats, solver,
+    sudoku::{Board, Cell, Difficulty, Sudoku},
+};
+
+use crate::export::{self, ExportFormat};
+
+/// Sudoku in the terminal.
+///
+/// Puzzles are read and written in the single-line format: 81 cells, row by
+/// row, with `.` or `0` for empty cells. Commands that take a puzzle also
+/// accept the path to a `.ss`, `.sdk` or `.sdm` file.
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Default)]
+pub enum Command {
+    /// Play in the terminal (default)
+    #[default]
+    Play,
+    /// Print the solution of a puzzle
+    Solve {
+        /// Puzzle or puzzle file to solve, read line by line from stdin if omitted
+        puzzle: Option<String>,
+    },
+    /// Print new puzzles, one per line, and the seed of each to stderr
+    Generate {
+        #[arg(short, long, default_value = "easy")]
+        difficulty: Difficulty,
+        #[arg(short = 'n', long, default_value_t = 1)]
+        count: usize,
+        /// Build the same puzzles every time; each puzzle uses the next seed
+        #[arg(short, long)]
+        seed: Option<u64>,
+    },
+    /// Print the difficulty, score and hardest technique needed by a puzzle
+    Rate {
+        /// Puzzle or puzzle file to rate, read line by line from stdin if omitted
+        puzzle: Option<String>,
+    },
+    /// Report whether a puzzle has no solution, a unique one or several
+    Validate {
+        /// Puzzle or puzzle file to validate, read line by line from stdin if omitted
+        puzzle: Option<String>,
+    },
+    /// Export puzzles as text, SVG or a printable PDF
+    Export {
+        /// Puzzle or puzzle file to export, read line by line from stdin if omitted
+        puzzle: Option<String>,
+        /// File to write, guessing the format from its extension, or stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// One of ascii, unicode, svg or pdf
+        #[arg(short, long)]
+        format: Option<ExportFormat>,
+    },
+}
+
+impl Command {
+    /// Runs a non-interactive command. Fails when any of the given puzzles
+    /// can't be solved, or for `validate`, doesn't have a unique solution.
+    pub fn run(self) -> Result<ExitCode> {
+        let ok = match self {
+            Self::Play => unreachable!("the TUI is started from main"),
+            Self::Solve { puzzle } => for_each_puzzle(puzzle, solve)?,
+            Self::Rate { puzzle } => for_each_puzzle(puzzle, rate)?,
+            Self::Validate { puzzle } => for_each_puzzle(puzzle, validate)?,
+            Self::Export {
+                puzzle,
+                output,
+                format,
+            } => {
+                export_puzzles(read_puzzles(puzzle)?, output, format)?;
+                true
+            }
+            Self::Generate {
+                difficulty,
+                count,
+                seed,
+            } => {
+                for i in 0..count as u64 {
+                    let game = match seed {
+                        Some(seed) => Sudoku::generate_seeded(difficulty, seed.wrapping_add(i)),
+                        None => Sudoku::generate(difficulty),
+                    };
+                    if let Some(seed) = game.seed() {
+                        eprintln!("seed {seed}");
+                    }
+                    println!("{}", game.puzzle());
+                }
+                true
+            }
+        };
+        Ok(if ok {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        })
+    }
+}
+
+fn read_puzzles(puzzle: Option<String>) -> Result<Vec<Board>> {
+    match puzzle {
+        Some(path) if Path::new(&path).is_file() => formats::read(Path::new(&path)),
+        Some(puzzle) => Ok(vec![puzzle.parse()?]),
+        None => formats::parse(&io::read_to_string(io::stdin())?, formats::Format::Lines),
+    }
+}
+
+fn for_each_puzzle(puzzle: Option<String>, f: fn(Board) -> bool) -> Result<bool> {
+    let mut ok = true;
+    for puzzle in read_puzzles(puzzle)? {
+        ok &= f(puzzle);
+    }
+    Ok(ok)
+}
+
+fn export_puzzles(
+    puzzles: Vec<Board>,
+    output: Option<PathBuf>,
+    format: Option<ExportFormat>,
+) -> Result<()> {
+    let format = match (format, &output) {
+        (Some(format), _) => format,
+        (None, Some(path)) => ExportFormat::from_path(path)?,
+        (None, None) => ExportFormat::Unicode,
+    };
+    let grids: Vec<_> = puzzles
+        .iter()
+        .map(|puzzle| puzzle.grid().map(|row| row.map(Cell::new)))
+        .collect();
+    let bytes = export::export(&grids, format);
+
+    match output {
+        Some(path) => fs::write(path, bytes)?,
+        None => io::stdout().write_all(&bytes)?,
+    }
+    Ok(())
+}
+
+fn solve(mut board: Board) -> bool {
+    if !board.solve() {
+        eprintln!("{board}: no solution");
+        return false;
+    }
+    println!("{board}");
+    true
+}
+
+fn rate(board: Board) -> bool {
+    if board.has_conflicts() || board.clone().count_solutions(1) == 0 {
+        eprintln!("{board}: no solution");
+        return false;
+    }
+    let rating = solver::rate(board.grid());
+    let difficulty = rating.difficulty().as_str();
+    println!("{difficulty}\t{}\t{}", rating.score, rating.as_str());
+    true
+}
+
+fn validate(board: Board) -> bool {
+    let solutions = if board.has_conflicts() {
+        0
+    } else {
+        board.clone().count_solutions(2)
+    };
+    match solutions {
+        0 => println!("no solution"),
+        1 => println!("unique solution"),
+        _ => println!("multiple solutions"),
+    }
+    solutions == 1
+}
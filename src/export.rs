@@ -0,0 +1,344 @@
+use std::{fmt::Write, path::Path};
+
+use anyhow::{anyhow, Result};
+use sudoku_core::sudoku::{Cell, SIZE, SUBGRID_SIZE};
+
+pub type Grid = [[Cell; SIZE]; SIZE];
+
+/// Formats a game can be exported to. Givens and the player's entries are
+/// always told apart: entries are wrapped in parentheses in text, and drawn
+/// in blue in SVG and PDF, which also show pencil marks.
+#[derive(Clone, Copy)]
+pub enum ExportFormat {
+    Ascii,
+    Unicode,
+    Svg,
+    Pdf,
+}
+
+impl ExportFormat {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        match extension.map(str::to_ascii_lowercase).as_deref() {
+            Some("txt") => Ok(Self::Unicode),
+            Some("svg") => Ok(Self::Svg),
+            Some("pdf") => Ok(Self::Pdf),
+            _ => Err(anyhow!("unknown export format, use .txt, .svg or .pdf")),
+        }
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "ascii" => Ok(Self::Ascii),
+            "unicode" | "txt" => Ok(Self::Unicode),
+            "svg" => Ok(Self::Svg),
+            "pdf" => Ok(Self::Pdf),
+            _ => Err(anyhow!("unknown export format '{s}'")),
+        }
+    }
+}
+
+pub fn export(grids: &[Grid], format: ExportFormat) -> Vec<u8> {
+    match format {
+        ExportFormat::Ascii => join(grids, |grid| text(grid, &ASCII), "\n"),
+        ExportFormat::Unicode => join(grids, |grid| text(grid, &UNICODE), "\n"),
+        ExportFormat::Svg => svg(grids).into_bytes(),
+        ExportFormat::Pdf => pdf(grids),
+    }
+}
+
+fn join(grids: &[Grid], f: impl Fn(&Grid) -> String, separator: &str) -> Vec<u8> {
+    grids
+        .iter()
+        .map(f)
+        .collect::<Vec<_>>()
+        .join(separator)
+        .into_bytes()
+}
+
+/// Characters used to draw a grid: corners and junctions of the top, middle
+/// and bottom borders, then the horizontal and vertical lines.
+struct BoxChars {
+    top: [char; 3],
+    middle: [char; 3],
+    bottom: [char; 3],
+    horizontal: char,
+    vertical: char,
+}
+
+const ASCII: BoxChars = BoxChars {
+    top: ['+', '+', '+'],
+    middle: ['+', '+', '+'],
+    bottom: ['+', '+', '+'],
+    horizontal: '-',
+    vertical: '|',
+};
+
+const UNICODE: BoxChars = BoxChars {
+    top: ['┌', '┬', '┐'],
+    middle: ['├', '┼', '┤'],
+    bottom: ['└', '┴', '┘'],
+    horizontal: '─',
+    vertical: '│',
+};
+
+fn text(grid: &Grid, chars: &BoxChars) -> String {
+    let border = |[left, junction, right]: [char; 3]| {
+        let segment = chars.horizontal.to_string().repeat(SUBGRID_SIZE * 3);
+        format!(
+            "{left}{}{right}\n",
+            [segment.as_str(); 3].join(&junction.to_string())
+        )
+    };
+
+    let mut out = border(chars.top);
+    for (y, row) in grid.iter().enumerate() {
+        if y % SUBGRID_SIZE == 0 && y != 0 {
+            out += &border(chars.middle);
+        }
+        for (x, cell) in row.iter().enumerate() {
+            if x % SUBGRID_SIZE == 0 {
+                out.push(chars.vertical);
+            }
+            out += &match cell.value {
+                0 => " . ".to_string(),
+                value if cell.writable() => format!("({value})"),
+                value => format!(" {value} "),
+            };
+        }
+        out.push(chars.vertical);
+        out.push('\n');
+    }
+    out + &border(chars.bottom)
+}
+
+/// Indices of the grid lines, thin ones first so that box lines are drawn
+/// over them where they cross.
+fn grid_lines() -> impl Iterator<Item = usize> {
+    (0..=SIZE)
+        .filter(|i| i % SUBGRID_SIZE != 0)
+        .chain((0..=SIZE).step_by(SUBGRID_SIZE))
+}
+
+const SVG_CELL: usize = 40;
+const SVG_MARGIN: usize = 20;
+const GIVEN_COLOR: &str = "#000000";
+const ENTRY_COLOR: &str = "#1d4ed8";
+const NOTE_COLOR: &str = "#6b7280";
+
+/// Draws every grid in a single column.
+fn svg(grids: &[Grid]) -> String {
+    let side = SVG_CELL * SIZE;
+    let width = side + 2 * SVG_MARGIN;
+    let height = grids.len() * (side + SVG_MARGIN) + SVG_MARGIN;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="Helvetica, Arial, sans-serif">"#
+    );
+    let _ = writeln!(
+        out,
+        r##"<rect width="{width}" height="{height}" fill="#ffffff"/>"##
+    );
+    for (i, grid) in grids.iter().enumerate() {
+        svg_grid(
+            &mut out,
+            grid,
+            SVG_MARGIN,
+            SVG_MARGIN + i * (side + SVG_MARGIN),
+        );
+    }
+    out + "</svg>\n"
+}
+
+fn svg_grid(out: &mut String, grid: &Grid, left: usize, top: usize) {
+    let side = SVG_CELL * SIZE;
+    for i in grid_lines() {
+        let (stroke, width) = if i % SUBGRID_SIZE == 0 {
+            ("#000000", 3)
+        } else {
+            ("#9ca3af", 1)
+        };
+        let offset = i * SVG_CELL;
+        let _ = writeln!(
+            out,
+            r#"<line x1="{}" y1="{top}" x2="{}" y2="{}" stroke="{stroke}" stroke-width="{width}" stroke-linecap="square"/>"#,
+            left + offset,
+            left + offset,
+            top + side
+        );
+        let _ = writeln!(
+            out,
+            r#"<line x1="{left}" y1="{}" x2="{}" y2="{}" stroke="{stroke}" stroke-width="{width}" stroke-linecap="square"/>"#,
+            top + offset,
+            left + side,
+            top + offset
+        );
+    }
+
+    for (y, row) in grid.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            let cx = left + x * SVG_CELL + SVG_CELL / 2;
+            let cy = top + y * SVG_CELL + SVG_CELL / 2;
+            if cell.value != 0 {
+                let (color, weight) = if cell.writable() {
+                    (ENTRY_COLOR, "normal")
+                } else {
+                    (GIVEN_COLOR, "bold")
+                };
+                let _ = writeln!(
+                    out,
+                    r#"<text x="{cx}" y="{cy}" font-size="24" font-weight="{weight}" fill="{color}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                    cell.value
+                );
+                continue;
+            }
+            for note in (1..=9).filter(|&note| cell.has_note(note)) {
+                let i = (note - 1) as usize;
+                let nx = cx + (i % 3) * SVG_CELL / 3 - SVG_CELL / 3;
+                let ny = cy + (i / 3) * SVG_CELL / 3 - SVG_CELL / 3;
+                let _ = writeln!(
+                    out,
+                    r#"<text x="{nx}" y="{ny}" font-size="10" fill="{NOTE_COLOR}" text-anchor="middle" dominant-baseline="central">{note}</text>"#
+                );
+            }
+        }
+    }
+}
+
+/// A4 in points.
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const PDF_GRID: f32 = 230.0;
+const PDF_COLUMNS: usize = 2;
+const PDF_ROWS: usize = 3;
+/// Width of a digit in Helvetica, relative to the font size.
+const DIGIT_WIDTH: f32 = 0.556;
+
+/// Lays the grids out on A4 pages, six to a page, using only the standard
+/// Helvetica fonts so nothing has to be embedded.
+fn pdf(grids: &[Grid]) -> Vec<u8> {
+    let per_page = PDF_COLUMNS * PDF_ROWS;
+    let pages: Vec<String> = grids
+        .chunks(per_page)
+        .map(|page| {
+            let mut content = String::new();
+            for (i, grid) in page.iter().enumerate() {
+                let gap_x = (PAGE_WIDTH - PDF_COLUMNS as f32 * PDF_GRID) / (PDF_COLUMNS + 1) as f32;
+                let gap_y = (PAGE_HEIGHT - PDF_ROWS as f32 * PDF_GRID) / (PDF_ROWS + 1) as f32;
+                let left = gap_x + (i % PDF_COLUMNS) as f32 * (PDF_GRID + gap_x);
+                let top = PAGE_HEIGHT - gap_y - (i / PDF_COLUMNS) as f32 * (PDF_GRID + gap_y);
+                pdf_grid(&mut content, grid, left, top);
+            }
+            content
+        })
+        .collect();
+
+    // Objects 1 to 4 are the catalog, the page tree and the two fonts, then
+    // every page is followed by its content stream.
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            (0..pages.len())
+                .map(|i| format!("{} 0 R", 5 + 2 * i))
+                .collect::<Vec<_>>()
+                .join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold >>".to_string(),
+    ];
+    for (i, content) in pages.iter().enumerate() {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+             /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+            6 + 2 * i
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{content}endstream",
+            content.len()
+        ));
+    }
+
+    let mut out = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        let _ = write!(out, "{} 0 obj\n{object}\nendobj\n", i + 1);
+    }
+    let xref = out.len();
+    let _ = write!(out, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(out, "{offset:010} 00000 n ");
+    }
+    let _ = write!(
+        out,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+        objects.len() + 1
+    );
+    out.into_bytes()
+}
+
+fn pdf_grid(out: &mut String, grid: &Grid, left: f32, top: f32) {
+    let cell = PDF_GRID / SIZE as f32;
+    let bottom = top - PDF_GRID;
+
+    for i in grid_lines() {
+        let (gray, width) = if i % SUBGRID_SIZE == 0 {
+            (0.0, 2.0)
+        } else {
+            (0.6, 0.5)
+        };
+        let offset = i as f32 * cell;
+        let _ = writeln!(out, "{gray} G {width} w");
+        let _ = writeln!(
+            out,
+            "{} {bottom} m {} {top} l S",
+            left + offset,
+            left + offset
+        );
+        let _ = writeln!(
+            out,
+            "{left} {} m {} {} l S",
+            top - offset,
+            left + PDF_GRID,
+            top - offset
+        );
+    }
+
+    let mut text = |font: &str, size: f32, color: &str, x: f32, y: f32, digit: u8| {
+        let x = x - DIGIT_WIDTH * size / 2.0;
+        let y = y - 0.35 * size;
+        let _ = writeln!(
+            out,
+            "BT {color} rg /{font} {size} Tf {x} {y} Td ({digit}) Tj ET"
+        );
+    };
+
+    for (y, row) in grid.iter().enumerate() {
+        for (x, c) in row.iter().enumerate() {
+            let cx = left + (x as f32 + 0.5) * cell;
+            let cy = top - (y as f32 + 0.5) * cell;
+            if c.value != 0 {
+                if c.writable() {
+                    text("F1", 16.0, "0.11 0.31 0.85", cx, cy, c.value);
+                } else {
+                    text("F2", 16.0, "0 0 0", cx, cy, c.value);
+                }
+                continue;
+            }
+            for note in (1..=9).filter(|&note| c.has_note(note)) {
+                let i = (note - 1) as f32;
+                let nx = cx + (i % 3.0 - 1.0) * cell / 3.0;
+                let ny = cy - ((i / 3.0).floor() - 1.0) * cell / 3.0;
+                text("F1", 6.0, "0.42 0.45 0.5", nx, ny, note);
+            }
+        }
+    }
+}
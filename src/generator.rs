@@ -0,0 +1,132 @@
+use std::{
+    sync::{
+        mpsc::{self, Receiver, SendError},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+};
+
+use sudoku_core::sudoku::{Difficulty, Sudoku};
+
+/// Number of puzzles kept ready for each difficulty.
+const POOL_SIZE: usize = 2;
+
+/// Builds puzzles on background threads, keeping a few of each difficulty
+/// ready so a new game can start at once.
+#[derive(Default)]
+pub struct Generator {
+    pool: Arc<Pool>,
+}
+
+#[derive(Default)]
+struct Pool {
+    /// Ready games, by difficulty.
+    games: Mutex<[Vec<Sudoku>; Difficulty::ALL.len()]>,
+    /// Wakes the worker when a game is taken.
+    taken: Condvar,
+}
+
+impl Pool {
+    fn take(&self, difficulty: Difficulty) -> Option<Sudoku> {
+        let game = self.games.lock().unwrap()[difficulty as usize].pop();
+        self.taken.notify_one();
+        game
+    }
+
+    /// Keeps `game` for the next request of `difficulty`. It's filed under
+    /// the difficulty asked for even if generation settled for another
+    /// rating, as it would have been handed out directly.
+    fn put(&self, difficulty: Difficulty, game: Sudoku) {
+        let mut games = self.games.lock().unwrap();
+        let games = &mut games[difficulty as usize];
+        if games.len() < POOL_SIZE {
+            games.push(game);
+        }
+    }
+
+    /// Waits until a difficulty runs short of games, easiest first.
+    fn next_missing(&self) -> Difficulty {
+        let mut games = self.games.lock().unwrap();
+        loop {
+            let missing = Difficulty::ALL
+                .into_iter()
+                .find(|&difficulty| games[difficulty as usize].len() < POOL_SIZE);
+            if let Some(difficulty) = missing {
+                return difficulty;
+            }
+            games = self.taken.wait(games).unwrap();
+        }
+    }
+}
+
+impl Generator {
+    /// Starts filling the pool, and topping it up whenever a game is taken.
+    pub fn start(&self) {
+        let pool = Arc::clone(&self.pool);
+        thread::spawn(move || loop {
+            let difficulty = pool.next_missing();
+            pool.put(difficulty, Sudoku::generate(difficulty));
+        });
+    }
+
+    /// Receives a game of `difficulty`, straight from the pool when one is
+    /// ready and built on a new thread otherwise.
+    pub fn generate(&self, difficulty: Difficulty) -> Receiver<Sudoku> {
+        let (sender, receiver) = mpsc::channel();
+        match self.pool.take(difficulty) {
+            Some(game) => {
+                let _ = sender.send(game);
+            }
+            None => {
+                let pool = Arc::clone(&self.pool);
+                thread::spawn(move || {
+                    // Keep the game for next time if the wait was cancelled.
+                    if let Err(SendError(game)) = sender.send(Sudoku::generate(difficulty)) {
+                        pool.put(difficulty, game);
+                    }
+                });
+            }
+        }
+        receiver
+    }
+}
+
+/// Runs `build` on a new thread, sending the game once it's ready.
+pub fn spawn(build: impl FnOnce() -> Sudoku + Send + 'static) -> Receiver<Sudoku> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(build());
+    });
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    #[test]
+    fn fills_each_pool_with_its_difficulty() {
+        let generator = Generator::default();
+        generator.start();
+
+        let deadline = Instant::now() + Duration::from_secs(60);
+        loop {
+            let games = generator.pool.games.lock().unwrap();
+            if games.iter().all(|games| games.len() == POOL_SIZE) {
+                for difficulty in Difficulty::ALL {
+                    let games = &games[difficulty as usize];
+                    assert!(games.iter().all(|game| game.difficulty() == difficulty));
+                }
+                break;
+            }
+            drop(games);
+            assert!(Instant::now() < deadline, "the pool never filled up");
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let game = generator.generate(Difficulty::Hard).recv().unwrap();
+        assert!(game.difficulty() == Difficulty::Hard);
+    }
+}
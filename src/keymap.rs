@@ -0,0 +1,552 @@
+use std::{collections::BTreeMap, fmt, fs, io, path::PathBuf, str::FromStr};
+
+use anyhow::{anyhow, bail, Context, Result};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+/// Something a key can be bound to.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Command {
+    MoveLeft,
+    MoveDown,
+    MoveUp,
+    MoveRight,
+    /// Puts a digit from 1 to 9 in the selected cell.
+    Digit(u8),
+    ClearCell,
+    ToggleNotes,
+    Undo,
+    Redo,
+    Bookmark,
+    Rollback,
+    TogglePause,
+    Hint,
+    Check,
+    ToggleConflicts,
+    Solve,
+    NewGame,
+    ClearBoard,
+    SaveGame,
+    Export,
+    ToggleHistory,
+    ToggleControls,
+    /// Renames the selected save.
+    Rename,
+    /// Deletes the selected save, once pressed twice.
+    Delete,
+    /// Picks the selected entry of a menu or list.
+    Select,
+    /// Leaves the current screen.
+    Quit,
+}
+
+const DIGIT_NAMES: [&str; 9] = [
+    "digit-1", "digit-2", "digit-3", "digit-4", "digit-5", "digit-6", "digit-7", "digit-8",
+    "digit-9",
+];
+
+impl Command {
+    pub const ALL: [Self; 34] = [
+        Self::MoveLeft,
+        Self::MoveDown,
+        Self::MoveUp,
+        Self::MoveRight,
+        Self::Digit(1),
+        Self::Digit(2),
+        Self::Digit(3),
+        Self::Digit(4),
+        Self::Digit(5),
+        Self::Digit(6),
+        Self::Digit(7),
+        Self::Digit(8),
+        Self::Digit(9),
+        Self::ClearCell,
+        Self::ToggleNotes,
+        Self::Undo,
+        Self::Redo,
+        Self::Bookmark,
+        Self::Rollback,
+        Self::TogglePause,
+        Self::Hint,
+        Self::Check,
+        Self::ToggleConflicts,
+        Self::Solve,
+        Self::NewGame,
+        Self::ClearBoard,
+        Self::SaveGame,
+        Self::Export,
+        Self::ToggleHistory,
+        Self::ToggleControls,
+        Self::Rename,
+        Self::Delete,
+        Self::Select,
+        Self::Quit,
+    ];
+
+    /// Name of the command in the config file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::MoveLeft => "move-left",
+            Self::MoveDown => "move-down",
+            Self::MoveUp => "move-up",
+            Self::MoveRight => "move-right",
+            Self::Digit(digit) => DIGIT_NAMES[digit as usize - 1],
+            Self::ClearCell => "clear-cell",
+            Self::ToggleNotes => "notes",
+            Self::Undo => "undo",
+            Self::Redo => "redo",
+            Self::Bookmark => "bookmark",
+            Self::Rollback => "rollback",
+            Self::TogglePause => "pause",
+            Self::Hint => "hint",
+            Self::Check => "check",
+            Self::ToggleConflicts => "conflicts",
+            Self::Solve => "solve",
+            Self::NewGame => "new-game",
+            Self::ClearBoard => "clear-board",
+            Self::SaveGame => "save",
+            Self::Export => "export",
+            Self::ToggleHistory => "history",
+            Self::ToggleControls => "controls",
+            Self::Rename => "rename",
+            Self::Delete => "delete",
+            Self::Select => "select",
+            Self::Quit => "quit",
+        }
+    }
+}
+
+impl FromStr for Command {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|command| command.name() == s)
+            .ok_or_else(|| anyhow!("unknown command {s:?}"))
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A key with the modifiers held down, like `ctrl-r`. Shifted characters
+/// are written as the character they type, so `C` rather than `shift-c`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    fn matches(&self, event: KeyEvent) -> bool {
+        let mut modifiers = event.modifiers;
+        // Terminals report shifted characters both as the character and
+        // with the shift modifier.
+        if let KeyCode::Char(_) = event.code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        self.code == event.code && self.modifiers == modifiers
+    }
+}
+
+const NAMED_KEYS: [(&str, KeyCode); 13] = [
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("tab", KeyCode::Tab),
+    ("space", KeyCode::Char(' ')),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("insert", KeyCode::Insert),
+];
+
+impl FromStr for KeyChord {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut key = s;
+        // The key itself may be `-`, so only split off modifiers before it.
+        while let Some((modifier, rest)) = key.split_once('-').filter(|(_, rest)| !rest.is_empty())
+        {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => bail!("unknown modifier {modifier:?} in key {s:?}"),
+            };
+            key = rest;
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => {
+                let name = key.to_ascii_lowercase();
+                let function = name.strip_prefix('f').and_then(|n| n.parse().ok());
+                match NAMED_KEYS.iter().find(|(key, _)| *key == name) {
+                    Some(&(_, code)) => code,
+                    None => KeyCode::F(function.ok_or_else(|| anyhow!("unknown key {s:?}"))?),
+                }
+            }
+        };
+
+        // `shift-c` types the same `C` the event reports, while `ctrl-R` is
+        // reported as `ctrl-r`.
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => {
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            code => code,
+        };
+        Ok(Self { code, modifiers })
+    }
+}
+
+/// Short form for the controls legend, like `^R` or `←`.
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("^")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("M-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            f.write_str("S-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) if self.modifiers.contains(KeyModifiers::CONTROL) => {
+                write!(f, "{}", c.to_ascii_uppercase())
+            }
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::Left => f.write_str("←"),
+            KeyCode::Right => f.write_str("→"),
+            KeyCode::Up => f.write_str("↑"),
+            KeyCode::Down => f.write_str("↓"),
+            KeyCode::Backspace => f.write_str("Bksp"),
+            KeyCode::Delete => f.write_str("Del"),
+            KeyCode::Insert => f.write_str("Ins"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// A built-in set of bindings.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    /// hjkl and the arrow keys.
+    #[default]
+    Default,
+    /// hjkl only, with vim's redo and marks.
+    Vim,
+    /// The arrow keys and WASD, with the usual undo and redo shortcuts.
+    Arrows,
+}
+
+type Bindings = &'static [(Command, &'static [&'static str])];
+
+const DIGITS: Bindings = &[
+    (Command::Digit(1), &["1"]),
+    (Command::Digit(2), &["2"]),
+    (Command::Digit(3), &["3"]),
+    (Command::Digit(4), &["4"]),
+    (Command::Digit(5), &["5"]),
+    (Command::Digit(6), &["6"]),
+    (Command::Digit(7), &["7"]),
+    (Command::Digit(8), &["8"]),
+    (Command::Digit(9), &["9"]),
+];
+
+const DEFAULT: Bindings = &[
+    (Command::MoveLeft, &["Left", "h"]),
+    (Command::MoveDown, &["Down", "j"]),
+    (Command::MoveUp, &["Up", "k"]),
+    (Command::MoveRight, &["Right", "l"]),
+    (Command::ClearCell, &["x"]),
+    (Command::ToggleNotes, &["n"]),
+    (Command::Undo, &["u"]),
+    (Command::Redo, &["r"]),
+    (Command::Bookmark, &["b"]),
+    (Command::Rollback, &["B"]),
+    (Command::TogglePause, &["p"]),
+    (Command::Hint, &["t"]),
+    (Command::Check, &["c"]),
+    (Command::ToggleConflicts, &["a"]),
+    (Command::Solve, &["C"]),
+    (Command::NewGame, &["N"]),
+    (Command::ClearBoard, &["X"]),
+    (Command::SaveGame, &["S"]),
+    (Command::Export, &["E"]),
+    (Command::ToggleHistory, &["H"]),
+    (Command::ToggleControls, &["?"]),
+    (Command::Rename, &["R"]),
+    (Command::Delete, &["D"]),
+    (Command::Select, &["Enter"]),
+    (Command::Quit, &["Esc", "Q"]),
+];
+
+const VIM: Bindings = &[
+    (Command::MoveLeft, &["h"]),
+    (Command::MoveDown, &["j"]),
+    (Command::MoveUp, &["k"]),
+    (Command::MoveRight, &["l"]),
+    (Command::ClearCell, &["x"]),
+    (Command::ToggleNotes, &["n"]),
+    (Command::Undo, &["u"]),
+    (Command::Redo, &["ctrl-r"]),
+    (Command::Bookmark, &["m"]),
+    (Command::Rollback, &["'"]),
+    (Command::TogglePause, &["p"]),
+    (Command::Hint, &["t"]),
+    (Command::Check, &["c"]),
+    (Command::ToggleConflicts, &["a"]),
+    (Command::Solve, &["C"]),
+    (Command::NewGame, &["N"]),
+    (Command::ClearBoard, &["X"]),
+    (Command::SaveGame, &["S"]),
+    (Command::Export, &["E"]),
+    (Command::ToggleHistory, &["H"]),
+    (Command::ToggleControls, &["?"]),
+    (Command::Rename, &["R"]),
+    (Command::Delete, &["D"]),
+    (Command::Select, &["Enter"]),
+    (Command::Quit, &["Esc", "Q"]),
+];
+
+const ARROWS: Bindings = &[
+    (Command::MoveLeft, &["Left", "a"]),
+    (Command::MoveDown, &["Down", "s"]),
+    (Command::MoveUp, &["Up", "w"]),
+    (Command::MoveRight, &["Right", "d"]),
+    (Command::ClearCell, &["Backspace", "Delete", "x"]),
+    (Command::ToggleNotes, &["n"]),
+    (Command::Undo, &["ctrl-z", "u"]),
+    (Command::Redo, &["ctrl-y", "r"]),
+    (Command::Bookmark, &["b"]),
+    (Command::Rollback, &["B"]),
+    (Command::TogglePause, &["Space", "p"]),
+    (Command::Hint, &["t"]),
+    (Command::Check, &["c"]),
+    (Command::ToggleConflicts, &["f"]),
+    (Command::Solve, &["C"]),
+    (Command::NewGame, &["N"]),
+    (Command::ClearBoard, &["X"]),
+    (Command::SaveGame, &["S"]),
+    (Command::Export, &["E"]),
+    (Command::ToggleHistory, &["H"]),
+    (Command::ToggleControls, &["?"]),
+    (Command::Rename, &["R"]),
+    (Command::Delete, &["D"]),
+    (Command::Select, &["Enter"]),
+    (Command::Quit, &["Esc", "Q"]),
+];
+
+impl Preset {
+    fn bindings(self) -> impl Iterator<Item = &'static (Command, &'static [&'static str])> {
+        let bindings = match self {
+            Self::Default => DEFAULT,
+            Self::Vim => VIM,
+            Self::Arrows => ARROWS,
+        };
+        DIGITS.iter().chain(bindings)
+    }
+}
+
+/// The keymap file: a preset to start from and the commands it rebinds.
+///
+/// ```toml
+/// preset = "vim"
+///
+/// [keys]
+/// undo = ["u", "ctrl-z"]
+/// conflicts = ["F2"]
+/// ```
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct Config {
+    preset: Preset,
+    keys: BTreeMap<String, Vec<String>>,
+}
+
+/// The keys bound to each command.
+#[derive(Clone)]
+pub struct Keymap {
+    keys: BTreeMap<Command, Vec<KeyChord>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::preset(Preset::default())
+    }
+}
+
+fn path() -> Result<PathBuf> {
+    let dir = dirs::config_dir().context("couldn't find the config directory")?;
+    Ok(dir.join("sudoku").join("keys.toml"))
+}
+
+impl Keymap {
+    pub fn preset(preset: Preset) -> Self {
+        let mut keys = BTreeMap::new();
+        for &(command, chords) in preset.bindings() {
+            let chords = chords.iter().map(|chord| chord.parse().unwrap()).collect();
+            keys.insert(command, chords);
+        }
+        Self { keys }
+    }
+
+    /// Reads the keymap file, falling back to the default preset when there
+    /// is none.
+    pub fn load() -> Result<Self> {
+        let path = path()?;
+        match fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text)
+                .with_context(|| format!("couldn't read keys from {}", path.display())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| format!("couldn't read {}", path.display())),
+        }
+    }
+
+    fn parse(text: &str) -> Result<Self> {
+        let config: Config = toml::from_str(text)?;
+        let mut keymap = Self::preset(config.preset);
+        for (command, chords) in config.keys {
+            let chords = chords
+                .iter()
+                .map(|chord| chord.parse())
+                .collect::<Result<_>>()?;
+            keymap.keys.insert(command.parse()?, chords);
+        }
+        keymap.check()?;
+        Ok(keymap)
+    }
+
+    /// Fails when a key is bound to more than one command.
+    fn check(&self) -> Result<()> {
+        let mut bound = Vec::new();
+        for (&command, chords) in &self.keys {
+            for chord in chords {
+                if let Some((_, other)) = bound.iter().find(|(bound, _)| bound == chord) {
+                    bail!("{chord} is bound to both {other} and {command}");
+                }
+                bound.push((*chord, command));
+            }
+        }
+        Ok(())
+    }
+
+    /// The command bound to a key press, if any.
+    pub fn command(&self, event: KeyEvent) -> Option<Command> {
+        self.keys
+            .iter()
+            .find(|(_, chords)| chords.iter().any(|chord| chord.matches(event)))
+            .map(|(&command, _)| command)
+    }
+
+    /// The first key bound to a command, for showing in the UI.
+    pub fn key(&self, command: Command) -> Option<KeyChord> {
+        self.keys.get(&command)?.first().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(code: KeyCode, modifiers: KeyModifiers) -> KeyChord {
+        KeyChord { code, modifiers }
+    }
+
+    #[test]
+    fn parses_modifiers() {
+        let ctrl_r = chord(KeyCode::Char('r'), KeyModifiers::CONTROL);
+        assert_eq!("ctrl-r".parse::<KeyChord>().unwrap(), ctrl_r);
+        assert_eq!("Ctrl-R".parse::<KeyChord>().unwrap(), ctrl_r);
+        assert_eq!(
+            "shift-c".parse::<KeyChord>().unwrap(),
+            chord(KeyCode::Char('C'), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            "ctrl-alt-left".parse::<KeyChord>().unwrap(),
+            chord(KeyCode::Left, KeyModifiers::CONTROL | KeyModifiers::ALT)
+        );
+        assert_eq!(
+            "ctrl--".parse::<KeyChord>().unwrap(),
+            chord(KeyCode::Char('-'), KeyModifiers::CONTROL)
+        );
+    }
+
+    #[test]
+    fn parses_named_keys() {
+        let none = KeyModifiers::NONE;
+        assert_eq!(
+            "Esc".parse::<KeyChord>().unwrap(),
+            chord(KeyCode::Esc, none)
+        );
+        assert_eq!(
+            "space".parse::<KeyChord>().unwrap(),
+            chord(KeyCode::Char(' '), none)
+        );
+        assert_eq!(
+            "F2".parse::<KeyChord>().unwrap(),
+            chord(KeyCode::F(2), none)
+        );
+        assert_eq!(
+            "-".parse::<KeyChord>().unwrap(),
+            chord(KeyCode::Char('-'), none)
+        );
+        assert_eq!(
+            "B".parse::<KeyChord>().unwrap(),
+            chord(KeyCode::Char('B'), none)
+        );
+    }
+
+    #[test]
+    fn rejects_bad_keys() {
+        for bad in ["", "meta-x", "ctrl-", "escape", "Fx", "ctrl-nope"] {
+            assert!(bad.parse::<KeyChord>().is_err(), "{bad:?} parsed");
+        }
+    }
+
+    #[test]
+    fn matches_shifted_characters() {
+        let shifted = KeyEvent::new(KeyCode::Char('B'), KeyModifiers::SHIFT);
+        assert!("B".parse::<KeyChord>().unwrap().matches(shifted));
+        assert!(!"b".parse::<KeyChord>().unwrap().matches(shifted));
+    }
+
+    #[test]
+    fn presets_bind_each_key_once() {
+        for preset in [Preset::Default, Preset::Vim, Preset::Arrows] {
+            Keymap::preset(preset).check().unwrap();
+        }
+    }
+
+    #[test]
+    fn rejects_duplicate_bindings() {
+        let err = Keymap::parse("[keys]\nhint = [\"u\"]").err().unwrap();
+        assert!(format!("{err:#}").contains("bound to both"));
+
+        let keymap = Keymap::parse("preset = \"vim\"\n[keys]\nundo = [\"ctrl-z\"]").unwrap();
+        let ctrl_z = KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL);
+        assert_eq!(keymap.command(ctrl_z), Some(Command::Undo));
+        assert!(Keymap::parse("[keys]\nnot-a-command = [\"z\"]").is_err());
+    }
+}
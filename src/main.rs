@@ -1,16 +1,34 @@
+use std::process::ExitCode;
+
 use anyhow::Result;
+use clap::Parser;
 
 use app::App;
+use cli::{Cli, Command};
 
 mod app;
-mod sudoku;
+mod cli;
+mod export;
+mod generator;
+mod keymap;
+mod saves;
+mod settings;
+mod stats;
+mod theme;
+
+fn main() -> Result<ExitCode> {
+    match Cli::parse().command.unwrap_or_default() {
+        Command::Play => play(),
+        command => command.run(),
+    }
+}
 
-fn main() -> Result<()> {
+fn play() -> Result<ExitCode> {
     tui::init_panic_hook()?;
     let terminal = tui::init_terminal()?;
     App::default().run(terminal)?;
     tui::restore_terminal()?;
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
 
 mod tui {
@@ -0,0 +1,207 @@
+use std::{
+    cmp::Reverse,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{bail, Context, Result};
+
+use sudoku_core::{
+    save::Encoding,
+    sudoku::{Difficulty, Sudoku, SIZE},
+};
+
+const EXTENSION: &str = "save";
+/// Slots named with this extension are saved as JSON, and keep it in
+/// their name.
+const JSON_EXTENSION: &str = "json";
+
+/// A saved game in the save directory.
+pub struct Slot {
+    pub name: String,
+    pub modified: SystemTime,
+    /// What the listing shows about the game, if the file could be read.
+    pub summary: Option<Summary>,
+}
+
+pub struct Summary {
+    pub difficulty: Difficulty,
+    pub elapsed: Duration,
+    /// Percentage of cells holding a value.
+    pub filled: usize,
+}
+
+impl Summary {
+    fn new(game: &Sudoku) -> Self {
+        let filled = game
+            .grid()
+            .iter()
+            .flatten()
+            .filter(|c| c.value != 0)
+            .count();
+        Self {
+            difficulty: game.difficulty(),
+            elapsed: game.elapsed(),
+            filled: filled * 100 / (SIZE * SIZE),
+        }
+    }
+}
+
+/// Where saves are kept: a `saves` folder in the platform data directory.
+pub fn dir() -> Result<PathBuf> {
+    let dir = dirs::data_dir().context("couldn't find the data directory")?;
+    Ok(dir.join("sudoku").join("saves"))
+}
+
+fn path(dir: &Path, name: &str) -> Result<PathBuf> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && !name.contains(['/', '\\', ':'])
+        && !name.chars().any(char::is_control);
+    if !valid {
+        bail!("invalid save name: {name:?}");
+    }
+    match encoding(name) {
+        Encoding::Json => Ok(dir.join(name)),
+        Encoding::Binary => Ok(dir.join(format!("{name}.{EXTENSION}"))),
+    }
+}
+
+fn encoding(name: &str) -> Encoding {
+    match name.strip_suffix(JSON_EXTENSION) {
+        Some(stem) if stem.len() > 1 && stem.ends_with('.') => Encoding::Json,
+        _ => Encoding::Binary,
+    }
+}
+
+/// Lists the saved games in `dir`, most recently modified first.
+pub fn list(dir: &Path) -> Result<Vec<Slot>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut slots = Vec::new();
+    // Entries that can't be read are left out rather than failing the list.
+    for entry in fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        let name = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(EXTENSION) => path.file_stem(),
+            Some(JSON_EXTENSION) => path.file_name(),
+            _ => continue,
+        };
+        let Some(name) = name.and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let Ok(modified) = fs::metadata(&path).and_then(|metadata| metadata.modified()) else {
+            continue;
+        };
+        let summary = fs::read(&path)
+            .ok()
+            .and_then(|bytes| Sudoku::load(&bytes).ok())
+            .map(|game| Summary::new(&game));
+        slots.push(Slot {
+            name: name.to_string(),
+            modified,
+            summary,
+        });
+    }
+    slots.sort_by_key(|slot| Reverse(slot.modified));
+    Ok(slots)
+}
+
+pub fn save(dir: &Path, name: &str, game: &Sudoku) -> Result<()> {
+    let save = || {
+        let path = path(dir, name)?;
+        fs::create_dir_all(dir)?;
+        fs::write(&path, game.save(encoding(name))?)
+            .with_context(|| format!("couldn't write {}", path.display()))
+    };
+    save().context("Couldn't save the game")
+}
+
+pub fn load(dir: &Path, name: &str) -> Result<Sudoku> {
+    let path = path(dir, name)?;
+    let bytes = match fs::read(&path) {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => bail!("No saved game found"),
+        bytes => bytes.with_context(|| format!("couldn't read {}", path.display()))?,
+    };
+    Sudoku::load(&bytes).context("Save file is corrupt or from an incompatible version")
+}
+
+pub fn delete(dir: &Path, name: &str) -> Result<()> {
+    fs::remove_file(path(dir, name)?).map_err(Into::into)
+}
+
+/// Renames a slot, refusing to overwrite another one. Renaming to or from
+/// a `.json` name saves the game again in the encoding of the new name.
+pub fn rename(dir: &Path, from: &str, to: &str) -> Result<()> {
+    let (from_path, to_path) = (path(dir, from)?, path(dir, to)?);
+    if from_path == to_path {
+        return Ok(());
+    }
+    if to_path.exists() {
+        bail!("a save with that name already exists");
+    }
+    if encoding(from) == encoding(to) {
+        return fs::rename(from_path, to_path).map_err(Into::into);
+    }
+    save(dir, to, &load(dir, from)?)?;
+    fs::remove_file(from_path).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty folder of its own for each test.
+    fn scratch_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sudoku-{}-{test}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn is_json(path: PathBuf) -> bool {
+        fs::read(path).unwrap().starts_with(b"{")
+    }
+
+    #[test]
+    fn renames_into_the_new_encoding() {
+        let dir = scratch_dir("rename");
+        let game = Sudoku::generate_seeded(Difficulty::Easy, 1);
+        save(&dir, "rename", &game).unwrap();
+
+        rename(&dir, "rename", "renamed.json").unwrap();
+        assert!(!dir.join("rename.save").exists());
+        assert!(is_json(dir.join("renamed.json")));
+        assert!(load(&dir, "renamed.json").unwrap().puzzle() == game.puzzle());
+
+        rename(&dir, "renamed.json", "renamed").unwrap();
+        assert!(!dir.join("renamed.json").exists());
+        assert!(!is_json(dir.join("renamed.save")));
+        assert!(load(&dir, "renamed").unwrap().puzzle() == game.puzzle());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn lists_around_unreadable_entries() {
+        let dir = scratch_dir("list");
+        save(
+            &dir,
+            "listed",
+            &Sudoku::generate_seeded(Difficulty::Easy, 2),
+        )
+        .unwrap();
+        std::os::unix::fs::symlink(dir.join("missing"), dir.join("dangling.save")).unwrap();
+
+        let names: Vec<_> = list(&dir)
+            .unwrap()
+            .into_iter()
+            .map(|slot| slot.name)
+            .collect();
+        assert_eq!(names, ["listed"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,165 @@
+use std::{fs, io, path::PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sudoku_core::sudoku::{Difficulty, Limits};
+
+use crate::theme::Theme;
+
+/// Preferences changed from the settings screen, kept as TOML in the
+/// platform config directory.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Settings {
+    pub hint_limit: u8,
+    pub check_limit: u8,
+    pub show_timer: bool,
+    /// Whether digits and notes matching the one under the cursor stand out.
+    pub highlight_same_digit: bool,
+    /// Whether the game pauses when the terminal loses focus.
+    pub auto_pause: bool,
+    /// Name of the color theme.
+    pub theme: String,
+    /// Difficulty picked first in the new game menu.
+    pub difficulty: Difficulty,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let limits = Limits::default();
+        Self {
+            hint_limit: limits.hints,
+            check_limit: limits.checks,
+            show_timer: true,
+            highlight_same_digit: true,
+            auto_pause: true,
+            theme: Theme::default().name.to_string(),
+            difficulty: Difficulty::default(),
+        }
+    }
+}
+
+/// One line of the settings screen.
+#[derive(Clone, Copy)]
+pub enum Setting {
+    HintLimit,
+    CheckLimit,
+    ShowTimer,
+    HighlightSameDigit,
+    AutoPause,
+    Theme,
+    Difficulty,
+}
+
+fn path() -> Result<PathBuf> {
+    let dir = dirs::config_dir().context("couldn't find the config directory")?;
+    Ok(dir.join("sudoku").join("settings.toml"))
+}
+
+impl Settings {
+    /// Reads the settings, which are the defaults until they're changed.
+    pub fn load() -> Result<Self> {
+        let path = path()?;
+        match fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text)
+                .with_context(|| format!("couldn't read settings from {}", path.display())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| format!("couldn't read {}", path.display())),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, toml::to_string(self)?)
+            .with_context(|| format!("couldn't write {}", path.display()))
+    }
+
+    /// The chosen theme, or the default one when it's no longer around.
+    pub fn theme(&self) -> Theme {
+        Theme::by_name(&self.theme).unwrap_or_default()
+    }
+
+    pub fn limits(&self) -> Limits {
+        Limits {
+            checks: self.check_limit,
+            hints: self.hint_limit,
+        }
+    }
+}
+
+/// Moves `step` places through `options` from `current`, wrapping around.
+fn cycle<T: Copy>(options: &[T], current: Option<usize>, step: isize) -> T {
+    let current = current.unwrap_or(0) as isize;
+    options[(current + step).rem_euclid(options.len() as isize) as usize]
+}
+
+impl Setting {
+    pub const ALL: [Self; 7] = [
+        Self::HintLimit,
+        Self::CheckLimit,
+        Self::ShowTimer,
+        Self::HighlightSameDigit,
+        Self::AutoPause,
+        Self::Theme,
+        Self::Difficulty,
+    ];
+
+    /// Highest hint and check limit that can be set.
+    const MAX_LIMIT: u8 = 9;
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::HintLimit => "Hints",
+            Self::CheckLimit => "Checks",
+            Self::ShowTimer => "Show timer",
+            Self::HighlightSameDigit => "Highlight same digit",
+            Self::AutoPause => "Pause when unfocused",
+            Self::Theme => "Theme",
+            Self::Difficulty => "Default difficulty",
+        }
+    }
+
+    /// The current value, as shown in the settings screen.
+    pub fn value(self, settings: &Settings) -> String {
+        let on_off = |on: bool| if on { "On" } else { "Off" }.to_string();
+        match self {
+            Self::HintLimit => settings.hint_limit.to_string(),
+            Self::CheckLimit => settings.check_limit.to_string(),
+            Self::ShowTimer => on_off(settings.show_timer),
+            Self::HighlightSameDigit => on_off(settings.highlight_same_digit),
+            Self::AutoPause => on_off(settings.auto_pause),
+            Self::Theme => settings.theme().title.to_string(),
+            Self::Difficulty => settings.difficulty.as_str().to_string(),
+        }
+    }
+
+    /// Moves the value `step` choices forward or back. Limits stop at their
+    /// ends, the other choices wrap around.
+    pub fn change(self, settings: &mut Settings, step: isize) {
+        let limit = |value: u8| (value as isize + step).clamp(0, Self::MAX_LIMIT as isize) as u8;
+        match self {
+            Self::HintLimit => settings.hint_limit = limit(settings.hint_limit),
+            Self::CheckLimit => settings.check_limit = limit(settings.check_limit),
+            Self::ShowTimer => settings.show_timer = !settings.show_timer,
+            Self::HighlightSameDigit => {
+                settings.highlight_same_digit = !settings.highlight_same_digit
+            }
+            Self::AutoPause => settings.auto_pause = !settings.auto_pause,
+            Self::Theme => {
+                let current = Theme::ALL
+                    .iter()
+                    .position(|theme| theme.name == settings.theme);
+                settings.theme = cycle(&Theme::ALL, current, step).name.to_string();
+            }
+            Self::Difficulty => {
+                let current = Difficulty::ALL
+                    .iter()
+                    .position(|&difficulty| difficulty == settings.difficulty);
+                settings.difficulty = cycle(&Difficulty::ALL, current, step);
+            }
+        }
+    }
+}
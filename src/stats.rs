@@ -0,0 +1,175 @@
+use std::{collections::BTreeSet, fs, io, path::PathBuf, time::Duration};
+
+use anyhow::{Context, Result};
+use chrono::{Days, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sudoku_core::sudoku::{Difficulty, GameState, Sudoku};
+
+/// A finished game.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GameRecord {
+    pub difficulty: Difficulty,
+    /// Seconds played.
+    pub elapsed: u64,
+    pub hints: u8,
+    pub checks: u8,
+    /// Whether the player gave up and had the board filled in.
+    pub revealed: bool,
+    /// Whether conflict highlighting was used.
+    pub assisted: bool,
+    /// When the game was finished, or for daily puzzles, their date.
+    pub date: NaiveDate,
+}
+
+impl GameRecord {
+    pub fn new(game: &Sudoku) -> Self {
+        Self {
+            difficulty: game.difficulty(),
+            elapsed: game.elapsed().as_secs(),
+            hints: game.hints(),
+            checks: game.checks(),
+            revealed: matches!(game.state(), GameState::Solved),
+            assisted: game.assisted(),
+            date: game
+                .daily_date()
+                .unwrap_or_else(|| Local::now().date_naive()),
+        }
+    }
+
+    pub fn won(&self) -> bool {
+        !self.revealed
+    }
+}
+
+/// Every finished game, kept as JSON in the platform data directory.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Stats {
+    games: Vec<GameRecord>,
+    /// Daily puzzles, kept apart so they don't count towards the streak and
+    /// times of normal games.
+    #[serde(default)]
+    daily: Vec<GameRecord>,
+}
+
+/// Totals for the games of one difficulty.
+pub struct Summary {
+    pub played: usize,
+    pub won: usize,
+    /// Best and average time of the games won without assists.
+    pub best: Option<Duration>,
+    pub average: Option<Duration>,
+    /// Average time of the games won with conflict highlighting.
+    pub assisted_average: Option<Duration>,
+}
+
+fn path() -> Result<PathBuf> {
+    let dir = dirs::data_dir().context("couldn't find the data directory")?;
+    Ok(dir.join("sudoku").join("stats.json"))
+}
+
+impl Stats {
+    /// Reads the stats, which are empty until a game is finished.
+    pub fn load() -> Result<Self> {
+        let path = path()?;
+        match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .with_context(|| format!("couldn't read stats from {}", path.display())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| format!("couldn't read {}", path.display())),
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("couldn't write {}", path.display()))
+    }
+
+    /// Adds a finished game and stores the stats, returning whether it's a
+    /// new best time for its difficulty.
+    pub fn record(&mut self, game: &Sudoku) -> Result<bool> {
+        let record = GameRecord::new(game);
+        if game.daily_date().is_some() {
+            self.daily.push(record);
+            self.save()?;
+            return Ok(false);
+        }
+
+        let best = self.summary(record.difficulty).best;
+        let new_best = record.won()
+            && !record.assisted
+            && best.is_none_or(|best| record.elapsed < best.as_secs());
+        self.games.push(record);
+        self.save()?;
+        Ok(new_best)
+    }
+
+    pub fn summary(&self, difficulty: Difficulty) -> Summary {
+        let games: Vec<_> = self
+            .games
+            .iter()
+            .filter(|game| game.difficulty == difficulty)
+            .collect();
+        let times = |assisted: bool| {
+            games
+                .iter()
+                .filter(move |game| game.won() && game.assisted == assisted)
+                .map(|game| game.elapsed)
+        };
+        let average = |assisted| {
+            let count = times(assisted).count() as u64;
+            (count > 0).then(|| Duration::from_secs(times(assisted).sum::<u64>() / count))
+        };
+
+        Summary {
+            played: games.len(),
+            won: games.iter().filter(|game| game.won()).count(),
+            best: times(false).min().map(Duration::from_secs),
+            average: average(false),
+            assisted_average: average(true),
+        }
+    }
+
+    /// Number of different daily puzzles solved.
+    pub fn daily_won(&self) -> usize {
+        self.daily_dates().len()
+    }
+
+    /// Number of days in a row the daily puzzle was solved, up to today, or
+    /// yesterday while today's is still open.
+    pub fn daily_streak(&self) -> usize {
+        let won = self.daily_dates();
+        let today = Local::now().date_naive();
+        let mut day = if won.contains(&today) {
+            today
+        } else {
+            today - Days::new(1)
+        };
+        let mut streak = 0;
+        while won.contains(&day) {
+            streak += 1;
+            day = day - Days::new(1);
+        }
+        streak
+    }
+
+    fn daily_dates(&self) -> BTreeSet<NaiveDate> {
+        self.daily
+            .iter()
+            .filter(|game| game.won())
+            .map(|game| game.date)
+            .collect()
+    }
+
+    /// Number of games won in a row, up to the latest one.
+    pub fn streak(&self) -> usize {
+        self.games
+            .iter()
+            .rev()
+            .take_while(|game| game.won())
+            .count()
+    }
+}
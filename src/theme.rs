@@ -0,0 +1,183 @@
+use ratatui::{
+    layout::Alignment,
+    style::{
+        palette::tailwind::{AMBER, BLUE, EMERALD, RED, ROSE, SKY, SLATE},
+        Color, Style,
+    },
+    widgets::{block::Title, Block, Borders},
+};
+
+/// The colors of every part of the UI.
+#[derive(Clone, Copy)]
+pub struct Theme {
+    /// Name stored in the settings.
+    pub name: &'static str,
+    /// Name shown in the settings screen.
+    pub title: &'static str,
+    pub text: Color,
+    /// Undone moves, empty cells and notes.
+    pub dimmed: Color,
+    pub border: Color,
+    /// Digits of the puzzle.
+    pub given: Color,
+    /// Digits placed by the player.
+    pub digit: Color,
+    /// Checked digits, right and wrong.
+    pub correct: Color,
+    pub wrong: Color,
+    /// Digits that break the rules, when conflict highlighting is on.
+    pub conflict: Color,
+    /// Digits and notes matching the one under the cursor.
+    pub highlight: Color,
+    pub cursor_bg: Color,
+    /// Background of the row, column and box of the cursor.
+    pub cursor_line_bg: Color,
+    pub cursor_box_bg: Color,
+    pub hint: Color,
+    pub hint_cell_bg: Color,
+    pub hint_house_bg: Color,
+    pub hint_elimination_bg: Color,
+    pub won: Color,
+    pub pause: Color,
+    pub error: Color,
+    /// Text typed in input boxes.
+    pub input: Color,
+    /// Keys in the controls legend.
+    pub key: Color,
+    pub key_bg: Color,
+    pub legend_bg: Color,
+}
+
+impl Theme {
+    pub const DARK: Self = Self {
+        name: "dark",
+        title: "Dark",
+        text: SLATE.c400,
+        dimmed: Color::DarkGray,
+        border: SLATE.c400,
+        given: Color::White,
+        digit: Color::Blue,
+        correct: Color::Green,
+        wrong: Color::Red,
+        conflict: ROSE.c400,
+        highlight: Color::LightYellow,
+        cursor_bg: Color::DarkGray,
+        cursor_line_bg: Color::Black,
+        cursor_box_bg: Color::Black,
+        hint: SKY.c300,
+        hint_cell_bg: SKY.c700,
+        hint_house_bg: SKY.c950,
+        hint_elimination_bg: ROSE.c900,
+        won: EMERALD.c300,
+        pause: AMBER.c300,
+        error: ROSE.c300,
+        input: Color::White,
+        key: Color::White,
+        key_bg: Color::DarkGray,
+        legend_bg: Color::Black,
+    };
+
+    /// Dark text for terminals with a light background.
+    pub const LIGHT: Self = Self {
+        name: "light",
+        title: "Light",
+        text: SLATE.c600,
+        dimmed: SLATE.c400,
+        border: SLATE.c500,
+        given: Color::Black,
+        digit: BLUE.c700,
+        correct: EMERALD.c700,
+        wrong: RED.c700,
+        conflict: ROSE.c600,
+        highlight: AMBER.c600,
+        cursor_bg: SLATE.c300,
+        cursor_line_bg: SLATE.c100,
+        cursor_box_bg: SLATE.c100,
+        hint: SKY.c700,
+        hint_cell_bg: SKY.c200,
+        hint_house_bg: SKY.c50,
+        hint_elimination_bg: ROSE.c200,
+        won: EMERALD.c700,
+        pause: AMBER.c700,
+        error: ROSE.c700,
+        input: Color::Black,
+        key: Color::White,
+        key_bg: SLATE.c600,
+        legend_bg: SLATE.c100,
+    };
+
+    /// The Okabe–Ito palette, which avoids telling right from wrong by red
+    /// and green alone.
+    pub const COLOR_BLIND: Self = Self {
+        name: "color-blind",
+        title: "Color-blind safe",
+        correct: Color::Rgb(0x00, 0x9e, 0x73),
+        wrong: Color::Rgb(0xe6, 0x9f, 0x00),
+        digit: Color::Rgb(0x56, 0xb4, 0xe9),
+        conflict: Color::Rgb(0xd5, 0x5e, 0x00),
+        highlight: Color::Rgb(0xf0, 0xe4, 0x42),
+        hint: Color::Rgb(0x56, 0xb4, 0xe9),
+        hint_cell_bg: Color::Rgb(0x00, 0x72, 0xb2),
+        hint_house_bg: Color::Rgb(0x0c, 0x25, 0x3a),
+        hint_elimination_bg: Color::Rgb(0x5c, 0x2a, 0x00),
+        won: Color::Rgb(0x00, 0x9e, 0x73),
+        pause: Color::Rgb(0xf0, 0xe4, 0x42),
+        error: Color::Rgb(0xe6, 0x9f, 0x00),
+        ..Self::DARK
+    };
+
+    /// Only the basic terminal colors, for terminals without more.
+    pub const HIGH_CONTRAST: Self = Self {
+        name: "high-contrast",
+        title: "High contrast",
+        text: Color::White,
+        dimmed: Color::Gray,
+        border: Color::White,
+        given: Color::White,
+        digit: Color::LightCyan,
+        correct: Color::LightGreen,
+        wrong: Color::LightRed,
+        conflict: Color::LightMagenta,
+        highlight: Color::Yellow,
+        cursor_bg: Color::Blue,
+        cursor_line_bg: Color::Black,
+        cursor_box_bg: Color::Black,
+        hint: Color::LightCyan,
+        hint_cell_bg: Color::Cyan,
+        hint_house_bg: Color::DarkGray,
+        hint_elimination_bg: Color::Red,
+        won: Color::LightGreen,
+        pause: Color::Yellow,
+        error: Color::LightRed,
+        input: Color::White,
+        key: Color::Black,
+        key_bg: Color::White,
+        legend_bg: Color::Reset,
+    };
+
+    pub const ALL: [Self; 4] = [
+        Self::DARK,
+        Self::LIGHT,
+        Self::COLOR_BLIND,
+        Self::HIGH_CONTRAST,
+    ];
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|theme| theme.name == name)
+    }
+
+    /// A bordered box with a centered title.
+    pub fn block<'a>(&self, title: impl Into<Title<'a>>) -> Block<'a> {
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.border))
+            .title(title)
+            .title_alignment(Alignment::Center)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
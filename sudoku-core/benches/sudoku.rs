@@ -0,0 +1,74 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use sudoku_core::{
+    dlx::DancingLinks,
+    search::{Backtracking, Solver},
+    sudoku::{Board, Difficulty, Sudoku},
+};
+
+/// Puzzles with the fewest givens a Sudoku with one solution can have.
+const SEVENTEEN_CLUE: &str = include_str!("puzzles/17-clue.txt");
+/// Puzzles known to be hard for solvers, such as Easter Monster and
+/// AI Escargot.
+const HARD: &str = include_str!("puzzles/hard.txt");
+
+fn corpus(text: &str) -> Vec<Board> {
+    text.lines().map(|line| line.parse().unwrap()).collect()
+}
+
+fn generate(c: &mut Criterion) {
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    c.bench_function("full grid", |b| b.iter(|| Board::generate(&mut rng)));
+
+    let mut group = c.benchmark_group("puzzle");
+    for difficulty in Difficulty::ALL {
+        let mut seed = 0;
+        group.bench_function(BenchmarkId::from_parameter(difficulty.as_str()), |b| {
+            b.iter(|| {
+                seed += 1;
+                Sudoku::generate_seeded(difficulty, seed)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn uniqueness(c: &mut Criterion) {
+    let puzzles: Vec<Board> = (0..16)
+        .map(|seed| Sudoku::generate_seeded(Difficulty::Expert, seed).puzzle())
+        .collect();
+    c.bench_function("count_solutions", |b| {
+        b.iter(|| {
+            for puzzle in &puzzles {
+                black_box(puzzle.clone().count_solutions(2));
+            }
+        })
+    });
+}
+
+fn solve(c: &mut Criterion) {
+    let solvers: [(&str, &dyn Solver); 2] = [
+        ("backtracking", &Backtracking),
+        ("dancing links", &DancingLinks),
+    ];
+    for (name, text) in [("17-clue", SEVENTEEN_CLUE), ("hard", HARD)] {
+        let puzzles = corpus(text);
+        let mut group = c.benchmark_group(name);
+        for (solver_name, solver) in solvers {
+            group.bench_function(solver_name, |b| {
+                b.iter(|| {
+                    for puzzle in &puzzles {
+                        black_box(solver.count(puzzle.grid(), 2));
+                    }
+                })
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, generate, uniqueness, solve);
+criterion_main!(benches);
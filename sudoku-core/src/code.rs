@@ -0,0 +1,94 @@
+use anyhow::{bail, Result};
+
+use crate::sudoku::{Board, SIZE};
+
+const ALPHABET: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// An unsigned number of any size, least significant limb first.
+#[derive(Default)]
+struct Number(Vec<u32>);
+
+impl Number {
+    fn is_zero(&self) -> bool {
+        self.0.iter().all(|&limb| limb == 0)
+    }
+
+    /// Sets the number to `self * radix + digit`.
+    fn push(&mut self, radix: u32, digit: u32) {
+        let mut carry = digit as u64;
+        for limb in &mut self.0 {
+            let value = *limb as u64 * radix as u64 + carry;
+            *limb = value as u32;
+            carry = value >> 32;
+        }
+        if carry != 0 {
+            self.0.push(carry as u32);
+        }
+    }
+
+    /// Divides the number by `radix`, returning the remainder.
+    fn pop(&mut self, radix: u32) -> u32 {
+        let mut rem = 0u64;
+        for limb in self.0.iter_mut().rev() {
+            let value = (rem << 32) | *limb as u64;
+            *limb = (value / radix as u64) as u32;
+            rem = value % radix as u64;
+        }
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
+        rem as u32
+    }
+}
+
+/// A short code for sharing the givens of a puzzle. It's a number written
+/// in base 62: its lowest 81 bits say which cells are given, and the rest
+/// holds their values in base 9.
+pub fn encode(board: &Board) -> String {
+    let cells = board.grid().as_flattened();
+    let mut number = Number::default();
+    for &value in cells.iter().filter(|&&value| value != 0) {
+        number.push(9, value as u32 - 1);
+    }
+    for &value in cells {
+        number.push(2, (value != 0) as u32);
+    }
+
+    let mut code = Vec::new();
+    while !number.is_zero() {
+        code.push(ALPHABET[number.pop(62) as usize]);
+    }
+    code.reverse();
+    String::from_utf8(code).expect("the alphabet is ASCII")
+}
+
+/// Rebuilds the puzzle a code was made from.
+pub fn decode(code: &str) -> Result<Board> {
+    let mut number = Number::default();
+    for c in code.trim().bytes() {
+        let Some(digit) = ALPHABET.iter().position(|&a| a == c) else {
+            bail!("invalid character in code: {:?}", c as char);
+        };
+        number.push(62, digit as u32);
+    }
+
+    let mut cells = [0; SIZE * SIZE];
+    let mut given = [false; SIZE * SIZE];
+    for given in given.iter_mut().rev() {
+        *given = number.pop(2) == 1;
+    }
+    for (cell, _) in cells
+        .iter_mut()
+        .zip(given)
+        .filter(|(_, given)| *given)
+        .rev()
+    {
+        *cell = number.pop(9) as u8 + 1;
+    }
+    if !number.is_zero() {
+        bail!("code is too long");
+    }
+
+    let line: String = cells.iter().map(|&value| (b'0' + value) as char).collect();
+    line.parse()
+}
@@ -0,0 +1,303 @@
+use std::ops::ControlFlow;
+
+use crate::{
+    search::{Solver, Visit},
+    sudoku::{SIZE, SUBGRID_SIZE},
+};
+
+const CELLS: usize = SIZE * SIZE;
+/// One column per cell, and per digit of each row, column and box, each of
+/// which must be covered exactly once.
+const COLUMNS: usize = 4 * CELLS;
+/// One option per digit of each cell.
+const OPTIONS: usize = CELLS * SIZE;
+/// The root of the column list, followed by the column headers.
+const ROOT: usize = 0;
+
+/// Knuth's Algorithm X over the exact-cover form of Sudoku, with the matrix
+/// kept as Dancing Links.
+pub struct DancingLinks;
+
+/// The columns covered by placing `digit` (0 to 8) in `cell`.
+fn columns(cell: usize, digit: usize) -> [usize; 4] {
+    let (x, y) = (cell % SIZE, cell / SIZE);
+    let b = y / SUBGRID_SIZE * SUBGRID_SIZE + x / SUBGRID_SIZE;
+    [
+        cell,
+        CELLS + y * SIZE + digit,
+        2 * CELLS + x * SIZE + digit,
+        3 * CELLS + b * SIZE + digit,
+    ]
+}
+
+/// The sparse matrix, as nodes linked in four directions. Nodes `1..=COLUMNS`
+/// are the column headers, and the four nodes of each option follow.
+struct Links {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    /// Header of the column of each node.
+    column: Vec<usize>,
+    /// Option of each node, as `cell * SIZE + digit`.
+    option: Vec<usize>,
+    /// Options left in each column, by header.
+    size: Vec<usize>,
+    solution: [[u8; SIZE]; SIZE],
+}
+
+impl Links {
+    fn new() -> Self {
+        let nodes = 1 + COLUMNS + 4 * OPTIONS;
+        let mut links = Self {
+            left: Vec::with_capacity(nodes),
+            right: Vec::with_capacity(nodes),
+            up: Vec::with_capacity(nodes),
+            down: Vec::with_capacity(nodes),
+            column: Vec::with_capacity(nodes),
+            option: Vec::with_capacity(nodes),
+            size: vec![0; 1 + COLUMNS],
+            solution: [[0; SIZE]; SIZE],
+        };
+        for header in 0..=COLUMNS {
+            links.left.push((header + COLUMNS) % (COLUMNS + 1));
+            links.right.push((header + 1) % (COLUMNS + 1));
+            links.up.push(header);
+            links.down.push(header);
+            links.column.push(header);
+            links.option.push(0);
+        }
+        for option in 0..OPTIONS {
+            let first = links.left.len();
+            for (i, column) in columns(option / SIZE, option % SIZE)
+                .into_iter()
+                .enumerate()
+            {
+                let node = first + i;
+                let header = column + 1;
+                links.left.push(first + (i + 3) % 4);
+                links.right.push(first + (i + 1) % 4);
+                links.up.push(links.up[header]);
+                links.down.push(header);
+                links.column.push(header);
+                links.option.push(option);
+                let above = links.up[header];
+                links.down[above] = node;
+                links.up[header] = node;
+                links.size[header] += 1;
+            }
+        }
+        links
+    }
+
+    /// Removes a column, and every option that covers it from the others.
+    fn cover(&mut self, header: usize) {
+        let (left, right) = (self.left[header], self.right[header]);
+        self.right[left] = right;
+        self.left[right] = left;
+        let mut i = self.down[header];
+        while i != header {
+            let mut j = self.right[i];
+            while j != i {
+                let (up, down) = (self.up[j], self.down[j]);
+                self.down[up] = down;
+                self.up[down] = up;
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    /// Undoes [`Links::cover`], in reverse order.
+    fn uncover(&mut self, header: usize) {
+        let mut i = self.up[header];
+        while i != header {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                let (up, down) = (self.up[j], self.down[j]);
+                self.down[up] = j;
+                self.up[down] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+        let (left, right) = (self.left[header], self.right[header]);
+        self.right[left] = header;
+        self.left[right] = header;
+    }
+
+    /// Takes the option of `node` into the solution, covering the columns of
+    /// its other nodes.
+    fn choose(&mut self, node: usize) {
+        let option = self.option[node];
+        self.solution[option / SIZE / SIZE][option / SIZE % SIZE] = (option % SIZE) as u8 + 1;
+        let mut j = self.right[node];
+        while j != node {
+            self.cover(self.column[j]);
+            j = self.right[j];
+        }
+    }
+
+    fn unchoose(&mut self, node: usize) {
+        let mut j = self.left[node];
+        while j != node {
+            self.uncover(self.column[j]);
+            j = self.left[j];
+        }
+    }
+
+    /// The uncovered column with the fewest options.
+    fn smallest_column(&self) -> usize {
+        let mut best = self.right[ROOT];
+        let mut header = self.right[best];
+        while header != ROOT && self.size[best] > 1 {
+            if self.size[header] < self.size[best] {
+                best = header;
+            }
+            header = self.right[header];
+        }
+        best
+    }
+
+    fn search(&mut self, visit: Visit) -> ControlFlow<()> {
+        if self.right[ROOT] == ROOT {
+            return visit(&self.solution);
+        }
+        let header = self.smallest_column();
+        if self.size[header] == 0 {
+            return ControlFlow::Continue(());
+        }
+
+        self.cover(header);
+        let mut flow = ControlFlow::Continue(());
+        let mut node = self.down[header];
+        while node != header && flow.is_continue() {
+            self.choose(node);
+            flow = self.search(visit);
+            self.unchoose(node);
+            node = self.down[node];
+        }
+        self.uncover(header);
+        flow
+    }
+}
+
+impl Solver for DancingLinks {
+    fn solutions(&self, grid: &[[u8; SIZE]; SIZE], visit: Visit) {
+        let mut links = Links::new();
+        let mut covered = [false; COLUMNS];
+        for (cell, &value) in grid.iter().flatten().enumerate() {
+            if value == 0 {
+                continue;
+            }
+            let columns = columns(cell, value as usize - 1);
+            if columns.iter().any(|&column| covered[column]) {
+                return;
+            }
+            for column in columns {
+                covered[column] = true;
+            }
+            // The option's first node is in its cell column.
+            let node = 1 + COLUMNS + 4 * (cell * SIZE + value as usize - 1);
+            links.cover(links.column[node]);
+            links.choose(node);
+        }
+        let _ = links.search(visit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{search::Backtracking, sudoku::Board};
+
+    const HARD: &str = include_str!("../benches/puzzles/hard.txt");
+    const SEVENTEEN_CLUE: &str = include_str!("../benches/puzzles/17-clue.txt");
+
+    fn corpus() -> impl Iterator<Item = [[u8; SIZE]; SIZE]> {
+        HARD.lines()
+            .chain(SEVENTEEN_CLUE.lines())
+            .map(|line| *line.parse::<Board>().unwrap().grid())
+    }
+
+    fn cells() -> impl Iterator<Item = (usize, usize)> {
+        (0..SIZE).flat_map(|y| (0..SIZE).map(move |x| (x, y)))
+    }
+
+    fn assert_same_solutions(grid: &[[u8; SIZE]; SIZE]) {
+        let (mut dlx, mut backtracking) = (DancingLinks.all(grid), Backtracking.all(grid));
+        dlx.sort();
+        backtracking.sort();
+        assert_eq!(dlx, backtracking, "{grid:?}");
+    }
+
+    #[test]
+    fn agrees_on_the_corpus() {
+        for puzzle in corpus() {
+            assert_eq!(DancingLinks.count(&puzzle, 2), 1);
+            assert_same_solutions(&puzzle);
+        }
+    }
+
+    #[test]
+    fn agrees_on_several_solutions() {
+        // A 17-clue puzzle without one of its clues is never unique.
+        for puzzle in corpus().skip(HARD.lines().count()) {
+            let mut puzzle = puzzle;
+            let (x, y) = cells().find(|&(x, y)| puzzle[y][x] != 0).unwrap();
+            puzzle[y][x] = 0;
+            assert_eq!(DancingLinks.count(&puzzle, 2), 2);
+            assert_eq!(
+                DancingLinks.count(&puzzle, 50),
+                Backtracking.count(&puzzle, 50)
+            );
+        }
+
+        // A whole band left to fill has few enough solutions to list.
+        for puzzle in corpus() {
+            let mut grid = DancingLinks.all(&puzzle)[0];
+            grid[3..6].fill([0; SIZE]);
+            assert!(!DancingLinks.all(&grid).is_empty());
+            assert_same_solutions(&grid);
+        }
+    }
+
+    #[test]
+    fn agrees_on_no_solution() {
+        for puzzle in corpus() {
+            let solution = Backtracking.all(&puzzle)[0];
+            let board = Board::from_grid(puzzle).unwrap();
+            // A digit that breaks no rule yet, but isn't the solution's.
+            let (x, y, value) = cells()
+                .filter(|&(x, y)| puzzle[y][x] == 0)
+                .find_map(|(x, y)| {
+                    (1..=9)
+                        .find(|&value| value != solution[y][x] && board.is_valid(y, x, value))
+                        .map(|value| (x, y, value))
+                })
+                .unwrap();
+            let mut wrong = puzzle;
+            wrong[y][x] = value;
+            assert_eq!(DancingLinks.count(&wrong, 2), 0);
+            assert_eq!(Backtracking.count(&wrong, 2), 0);
+        }
+    }
+
+    #[test]
+    fn rejects_clashing_givens() {
+        for puzzle in corpus() {
+            let (x, y) = cells().find(|&(x, y)| puzzle[y][x] != 0).unwrap();
+            let value = puzzle[y][x];
+            let mut clashing = puzzle;
+            let (cx, _) = cells()
+                .find(|&(cx, cy)| cy == y && cx != x && puzzle[cy][cx] == 0)
+                .unwrap();
+            clashing[y][cx] = value;
+            assert_eq!(DancingLinks.count(&clashing, 2), 0);
+            assert_eq!(Backtracking.count(&clashing, 2), 0);
+            assert!(DancingLinks.all(&clashing).is_empty());
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::{fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+
+use crate::sudoku::{Board, SIZE};
+
+/// File formats puzzles can be imported from.
+#[derive(Clone, Copy)]
+pub enum Format {
+    /// One puzzle per line in the 81-character format. This covers plain
+    /// text files as well as `.sdm` collections.
+    Lines,
+    /// Simple Sudoku `.ss`: a 9x9 grid, optionally drawn with `|` and `-`
+    /// box separators.
+    SimpleSudoku,
+    /// SadMan Sudoku `.sdk`: a 9x9 grid after optional `#` metadata lines and
+    /// a `[Puzzle]` section header.
+    SadMan,
+}
+
+impl Format {
+    /// Picks the format from the file extension, defaulting to one puzzle per line.
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        match extension.map(str::to_ascii_lowercase).as_deref() {
+            Some("ss") => Self::SimpleSudoku,
+            Some("sdk") => Self::SadMan,
+            _ => Self::Lines,
+        }
+    }
+}
+
+/// Reads every puzzle in a file, guessing the format from its extension.
+pub fn read(path: &Path) -> Result<Vec<Board>> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("couldn't read {}", path.display()))?;
+    parse(&text, Format::from_path(path))
+}
+
+/// Reads every puzzle in `text`.
+pub fn parse(text: &str, format: Format) -> Result<Vec<Board>> {
+    let puzzles = match format {
+        Format::Lines => parse_lines(text)?,
+        Format::SimpleSudoku => vec![parse_grid(text.lines())?],
+        Format::SadMan => vec![parse_grid(sadman_puzzle(text))?],
+    };
+    if puzzles.is_empty() {
+        bail!("no puzzles found");
+    }
+    Ok(puzzles)
+}
+
+fn parse_lines(text: &str) -> Result<Vec<Board>> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| line.parse().with_context(|| format!("line {}", i + 1)))
+        .collect()
+}
+
+/// Lines of the `[Puzzle]` section, or of the whole file if it has no
+/// sections, without metadata.
+fn sadman_puzzle(text: &str) -> impl Iterator<Item = &str> {
+    let mut in_puzzle = true;
+    text.lines().filter(move |line| {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_puzzle = line.eq_ignore_ascii_case("[Puzzle]");
+            return false;
+        }
+        in_puzzle && !line.starts_with('#')
+    })
+}
+
+/// Reads a grid drawn over several lines, skipping anything that isn't a
+/// cell such as box separators.
+fn parse_grid<'a>(lines: impl Iterator<Item = &'a str>) -> Result<Board> {
+    let cells: String = lines
+        .flat_map(str::chars)
+        .filter_map(|c| match c {
+            '1'..='9' => Some(c),
+            '.' | '0' | 'x' | 'X' => Some('.'),
+            _ => None,
+        })
+        .collect();
+    if cells.len() != SIZE * SIZE {
+        bail!("expected {} cells, found {}", SIZE * SIZE, cells.len());
+    }
+    cells.parse()
+}
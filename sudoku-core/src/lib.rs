@@ -0,0 +1,29 @@
+//! Sudoku puzzles without a user interface: the board and game types,
+//! generation, solving and rating, and reading and writing puzzles and
+//! saved games.
+//!
+//! ```
+//! use sudoku_core::sudoku::{Board, Difficulty, Sudoku};
+//!
+//! let game = Sudoku::generate_seeded(Difficulty::Easy, 42);
+//! let puzzle: Board = game.puzzle();
+//! assert_eq!(puzzle.clone().count_solutions(2), 1);
+//! assert_eq!(puzzle.to_string().parse::<Board>().unwrap(), puzzle);
+//! ```
+
+#![warn(missing_docs)]
+
+/// Shareable base-62 codes for the givens of a puzzle.
+pub mod code;
+/// The Dancing Links exact-cover solver.
+pub mod dlx;
+/// Puzzle file formats from other Sudoku programs.
+pub mod formats;
+/// The versioned binary and JSON layouts of saved games.
+pub mod save;
+/// The [`Solver`](search::Solver) trait, and the bitmask search used by default.
+pub mod search;
+/// The logical solver, which explains hints and rates puzzles.
+pub mod solver;
+/// The board and game types and the generator.
+pub mod sudoku;
@@ -0,0 +1,427 @@
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDate;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::sudoku::{Bookmark, Cell, Change, Difficulty, Move, MoveKind, SIZE};
+
+/// Start of every binary save, followed by the format version as a
+/// little-endian `u16` and the bincode-encoded game.
+const MAGIC: &[u8; 8] = b"SUDOKUSV";
+/// Value of the `format` field in JSON saves.
+const JSON_FORMAT: &str = "sudoku-save";
+
+/// Version of the layout written by [`Save::encode`]. Bump it whenever
+/// [`Save`] changes and teach [`migrate`] to upgrade the previous layout.
+pub(crate) const VERSION: u16 = 6;
+
+/// How a game is written by [`Sudoku::save`](crate::sudoku::Sudoku::save).
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// Compact bincode.
+    #[default]
+    Binary,
+    /// Pretty-printed JSON that can be read and diffed.
+    Json,
+}
+
+/// A saved game, in the current layout.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Save {
+    pub grid: [[Cell; SIZE]; SIZE],
+    pub solution: [[u8; SIZE]; SIZE],
+    pub difficulty: Difficulty,
+    pub elapsed: u64,
+    pub checks: u8,
+    pub hints: u8,
+    pub history: Vec<Move>,
+    pub position: usize,
+    pub bookmarks: Vec<Bookmark>,
+    pub show_conflicts: bool,
+    pub assisted: bool,
+    pub seed: Option<u64>,
+    pub daily: Option<NaiveDate>,
+}
+
+/// Version 5, before rollbacks kept the bookmark they removed.
+#[derive(Deserialize)]
+struct SaveV5 {
+    grid: [[Cell; SIZE]; SIZE],
+    solution: [[u8; SIZE]; SIZE],
+    difficulty: Difficulty,
+    elapsed: u64,
+    checks: u8,
+    hints: u8,
+    history: Vec<MoveV5>,
+    position: usize,
+    bookmarks: Vec<Bookmark>,
+    show_conflicts: bool,
+    assisted: bool,
+    seed: Option<u64>,
+    daily: Option<NaiveDate>,
+}
+
+/// A move of versions 2 to 5, without a bookmark.
+#[derive(Deserialize)]
+struct MoveV5 {
+    kind: MoveKind,
+    changes: Vec<Change>,
+}
+
+impl From<SaveV5> for Save {
+    fn from(save: SaveV5) -> Self {
+        Self {
+            grid: save.grid,
+            solution: save.solution,
+            difficulty: save.difficulty,
+            elapsed: save.elapsed,
+            checks: save.checks,
+            hints: save.hints,
+            history: save
+                .history
+                .into_iter()
+                .map(|mv| Move::without_bookmark(mv.kind, mv.changes))
+                .collect(),
+            position: save.position,
+            bookmarks: save.bookmarks,
+            show_conflicts: save.show_conflicts,
+            assisted: save.assisted,
+            seed: save.seed,
+            daily: save.daily,
+        }
+    }
+}
+
+/// Version 4, without the seed and daily date.
+#[derive(Deserialize)]
+struct SaveV4 {
+    grid: [[Cell; SIZE]; SIZE],
+    solution: [[u8; SIZE]; SIZE],
+    difficulty: Difficulty,
+    elapsed: u64,
+    checks: u8,
+    hints: u8,
+    history: Vec<MoveV5>,
+    position: usize,
+    bookmarks: Vec<Bookmark>,
+    show_conflicts: bool,
+    assisted: bool,
+}
+
+impl From<SaveV4> for SaveV5 {
+    fn from(save: SaveV4) -> Self {
+        Self {
+            grid: save.grid,
+            solution: save.solution,
+            difficulty: save.difficulty,
+            elapsed: save.elapsed,
+            checks: save.checks,
+            hints: save.hints,
+            history: save.history,
+            position: save.position,
+            bookmarks: save.bookmarks,
+            show_conflicts: save.show_conflicts,
+            assisted: save.assisted,
+            seed: None,
+            daily: None,
+        }
+    }
+}
+
+/// Version 3, without conflict highlighting.
+#[derive(Deserialize)]
+struct SaveV3 {
+    grid: [[Cell; SIZE]; SIZE],
+    solution: [[u8; SIZE]; SIZE],
+    difficulty: Difficulty,
+    elapsed: u64,
+    checks: u8,
+    hints: u8,
+    history: Vec<MoveV5>,
+    position: usize,
+    bookmarks: Vec<Bookmark>,
+}
+
+impl From<SaveV3> for SaveV4 {
+    fn from(save: SaveV3) -> Self {
+        Self {
+            grid: save.grid,
+            solution: save.solution,
+            difficulty: save.difficulty,
+            elapsed: save.elapsed,
+            checks: save.checks,
+            hints: save.hints,
+            history: save.history,
+            position: save.position,
+            bookmarks: save.bookmarks,
+            show_conflicts: false,
+            assisted: false,
+        }
+    }
+}
+
+/// Version 2, without bookmarks.
+#[derive(Deserialize)]
+struct SaveV2 {
+    grid: [[Cell; SIZE]; SIZE],
+    solution: [[u8; SIZE]; SIZE],
+    difficulty: Difficulty,
+    elapsed: u64,
+    checks: u8,
+    hints: u8,
+    history: Vec<MoveV5>,
+    position: usize,
+}
+
+impl From<SaveV2> for SaveV3 {
+    fn from(save: SaveV2) -> Self {
+        Self {
+            grid: save.grid,
+            solution: save.solution,
+            difficulty: save.difficulty,
+            elapsed: save.elapsed,
+            checks: save.checks,
+            hints: save.hints,
+            history: save.history,
+            position: save.position,
+            bookmarks: Vec::new(),
+        }
+    }
+}
+
+/// Version 1, without move history.
+#[derive(Deserialize)]
+struct SaveV1 {
+    grid: [[Cell; SIZE]; SIZE],
+    solution: [[u8; SIZE]; SIZE],
+    difficulty: Difficulty,
+    elapsed: u64,
+    checks: u8,
+    hints: u8,
+}
+
+impl From<SaveV1> for SaveV2 {
+    fn from(save: SaveV1) -> Self {
+        Self {
+            grid: save.grid,
+            solution: save.solution,
+            difficulty: save.difficulty,
+            elapsed: save.elapsed,
+            checks: save.checks,
+            hints: save.hints,
+            history: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+/// Headerless saves from before versioning, whose cells had no notes.
+#[derive(Deserialize)]
+struct SaveV0 {
+    grid: [[CellV0; SIZE]; SIZE],
+    solution: [[u8; SIZE]; SIZE],
+    difficulty: Difficulty,
+    elapsed: u64,
+    checks: u8,
+    hints: u8,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+struct CellV0 {
+    value: u8,
+    flags: u8,
+}
+
+impl From<SaveV0> for SaveV1 {
+    fn from(save: SaveV0) -> Self {
+        Self {
+            grid: save
+                .grid
+                .map(|row| row.map(|cell| Cell::without_notes(cell.value, cell.flags))),
+            solution: save.solution,
+            difficulty: save.difficulty,
+            elapsed: save.elapsed,
+            checks: save.checks,
+            hints: save.hints,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonSave<'a> {
+    format: &'a str,
+    version: u16,
+    game: &'a Save,
+}
+
+#[derive(Deserialize)]
+struct JsonHeader {
+    format: String,
+    version: u16,
+    game: serde_json::Value,
+}
+
+/// The encoded game of some version, waiting to be read into its layout.
+enum Payload<'a> {
+    Binary(&'a [u8]),
+    Json(serde_json::Value),
+}
+
+impl Payload<'_> {
+    fn parse<T: DeserializeOwned>(self) -> Result<T> {
+        match self {
+            Self::Binary(bytes) => bincode::deserialize(bytes).map_err(Into::into),
+            Self::Json(value) => serde_json::from_value(value).map_err(Into::into),
+        }
+    }
+}
+
+impl Save {
+    pub fn encode(&self, encoding: Encoding) -> Result<Vec<u8>> {
+        match encoding {
+            Encoding::Binary => {
+                let mut bytes = MAGIC.to_vec();
+                bytes.extend(VERSION.to_le_bytes());
+                bincode::serialize_into(&mut bytes, self)?;
+                Ok(bytes)
+            }
+            Encoding::Json => {
+                let save = JsonSave {
+                    format: JSON_FORMAT,
+                    version: VERSION,
+                    game: self,
+                };
+                let mut bytes = serde_json::to_vec_pretty(&save)?;
+                bytes.push(b'\n');
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Reads a save in either encoding, upgrading it from older versions.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        if let Some(rest) = bytes.strip_prefix(MAGIC) {
+            let Some((version, payload)) = rest.split_first_chunk() else {
+                bail!("save file is truncated");
+            };
+            return migrate(u16::from_le_bytes(*version), Payload::Binary(payload));
+        }
+
+        if bytes.trim_ascii_start().starts_with(b"{") {
+            let save: JsonHeader = serde_json::from_slice(bytes)?;
+            if save.format != JSON_FORMAT {
+                bail!("not a sudoku save: {:?}", save.format);
+            }
+            return migrate(save.version, Payload::Json(save.game));
+        }
+
+        // Saves from before the header was introduced.
+        migrate(0, Payload::Binary(bytes))
+    }
+}
+
+/// Reads a payload written in format `version` and upgrades it to the
+/// current layout.
+fn migrate(version: u16, payload: Payload) -> Result<Save> {
+    let save = match version {
+        0 => payload
+            .parse::<SaveV0>()
+            .map(SaveV1::from)
+            .map(SaveV2::from)
+            .map(SaveV3::from)
+            .map(SaveV4::from)
+            .map(SaveV5::from)
+            .map(Save::from),
+        1 => payload
+            .parse::<SaveV1>()
+            .map(SaveV2::from)
+            .map(SaveV3::from)
+            .map(SaveV4::from)
+            .map(SaveV5::from)
+            .map(Save::from),
+        2 => payload
+            .parse::<SaveV2>()
+            .map(SaveV3::from)
+            .map(SaveV4::from)
+            .map(SaveV5::from)
+            .map(Save::from),
+        3 => payload
+            .parse::<SaveV3>()
+            .map(SaveV4::from)
+            .map(SaveV5::from)
+            .map(Save::from),
+        4 => payload.parse::<SaveV4>().map(SaveV5::from).map(Save::from),
+        5 => payload.parse::<SaveV5>().map(Save::from),
+        VERSION => payload.parse(),
+        _ => bail!("unsupported save format version {version}, this build reads up to {VERSION}"),
+    };
+    save.with_context(|| format!("couldn't read version {version} save"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Written by `Sudoku::save` before saves had a header or notes.
+    const SAVE_V0: &[u8] = include_bytes!("../tests/fixtures/save-v0.bin");
+
+    fn digits(grid: impl IntoIterator<Item = u8>) -> String {
+        grid.into_iter()
+            .map(|value| char::from(b'0' + value))
+            .collect()
+    }
+
+    #[test]
+    fn reads_headerless_saves() {
+        let save = Save::decode(SAVE_V0).unwrap();
+        assert_eq!(
+            digits(save.solution.into_iter().flatten()),
+            "823675941497281653516349728739456812682713495154892367375164289948527136261938574"
+        );
+        assert_eq!(
+            digits(save.grid.iter().flatten().map(|cell| cell.value)),
+            "920605900097000600000009728030000012600000000154002000305004280900020036260008004"
+        );
+        assert!(matches!(save.difficulty, Difficulty::Hard));
+        assert_eq!((save.elapsed, save.checks, save.hints), (754, 1, 1));
+
+        // The player's wrong 9, checked, next to a given 2.
+        let [wrong, given, ..] = save.grid[0];
+        assert!(wrong.writable() && wrong.checked() && !wrong.correct());
+        assert!(!given.writable() && !given.checked());
+        assert!(save.grid.iter().flatten().all(|cell| !cell.has_notes()));
+        assert!(save.history.is_empty() && save.seed.is_none());
+    }
+
+    #[test]
+    fn reads_moves_without_bookmarks() {
+        let mut game = crate::sudoku::Sudoku::generate_seeded(Difficulty::Easy, 1);
+        let (x, y) = (0..SIZE * SIZE)
+            .map(|i| (i % SIZE, i / SIZE))
+            .find(|&(x, y)| game.grid()[y][x].value == 0)
+            .unwrap();
+        game.update_cell(x, y, 5);
+
+        // The same game as version 5 wrote it, before moves had a bookmark.
+        let mut json: serde_json::Value =
+            serde_json::from_slice(&game.save(Encoding::Json).unwrap()).unwrap();
+        json["version"] = 5.into();
+        for mv in json["game"]["history"].as_array_mut().unwrap() {
+            mv.as_object_mut().unwrap().remove("bookmark");
+        }
+        let save = Save::decode(&serde_json::to_vec(&json).unwrap()).unwrap();
+        assert_eq!((save.history.len(), save.position), (1, 1));
+        assert!(save.history[0].kind == MoveKind::Edit);
+        assert_eq!(save.grid[y][x].value, 5);
+    }
+
+    #[test]
+    fn round_trips_both_encodings() {
+        let save = Save::decode(SAVE_V0).unwrap();
+        for encoding in [Encoding::Binary, Encoding::Json] {
+            let decoded = Save::decode(&save.encode(encoding).unwrap()).unwrap();
+            assert!(decoded.grid == save.grid);
+            assert_eq!(decoded.solution, save.solution);
+            assert_eq!(decoded.elapsed, save.elapsed);
+        }
+    }
+}
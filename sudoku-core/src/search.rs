@@ -0,0 +1,375 @@
+use std::ops::ControlFlow;
+
+use crate::sudoku::{SIZE, SUBGRID_SIZE};
+
+/// Called with each solution found, breaking to stop the search.
+pub type Visit<'a> = &'a mut dyn FnMut(&[[u8; SIZE]; SIZE]) -> ControlFlow<()>;
+
+/// A way of finding the solutions of a grid, where 0 is an empty cell.
+pub trait Solver {
+    /// Calls `visit` with every solution of `grid` until it breaks. Givens
+    /// that clash have no solution.
+    fn solutions(&self, grid: &[[u8; SIZE]; SIZE], visit: Visit);
+
+    /// Counts the solutions of `grid`, stopping at `limit`.
+    fn count(&self, grid: &[[u8; SIZE]; SIZE], limit: usize) -> usize {
+        let mut count = 0;
+        self.solutions(grid, &mut |_| {
+            count += 1;
+            if count < limit {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        });
+        count
+    }
+
+    /// Every solution of `grid`.
+    fn all(&self, grid: &[[u8; SIZE]; SIZE]) -> Vec<[[u8; SIZE]; SIZE]> {
+        let mut solutions = Vec::new();
+        self.solutions(grid, &mut |solution| {
+            solutions.push(*solution);
+            ControlFlow::Continue(())
+        });
+        solutions
+    }
+}
+
+/// Bitmask with a bit set for every digit from 1 to 9.
+const ALL_DIGITS: u16 = 0b11_1111_1110;
+const CELLS: usize = SIZE * SIZE;
+
+/// A grid being filled in, with the digits placed in each row, column and
+/// box kept as bitmasks so the candidates of a cell take three lookups.
+struct Grid {
+    cells: [u8; CELLS],
+    rows: [u16; SIZE],
+    cols: [u16; SIZE],
+    boxes: [u16; SIZE],
+    /// Cells filled since the search started, to empty them on the way back.
+    trail: Vec<u8>,
+}
+
+/// The cells of every row, column and box.
+const HOUSES: [[u8; SIZE]; 3 * SIZE] = houses();
+
+const fn houses() -> [[u8; SIZE]; 3 * SIZE] {
+    let mut houses = [[0; SIZE]; 3 * SIZE];
+    let mut i = 0;
+    while i < SIZE {
+        let mut j = 0;
+        while j < SIZE {
+            houses[i][j] = (i * SIZE + j) as u8;
+            houses[SIZE + i][j] = (j * SIZE + i) as u8;
+            let y = i / SUBGRID_SIZE * SUBGRID_SIZE + j / SUBGRID_SIZE;
+            let x = i % SUBGRID_SIZE * SUBGRID_SIZE + j % SUBGRID_SIZE;
+            houses[2 * SIZE + i][j] = (y * SIZE + x) as u8;
+            j += 1;
+        }
+        i += 1;
+    }
+    houses
+}
+
+fn box_of(cell: usize) -> usize {
+    let (x, y) = (cell % SIZE, cell / SIZE);
+    y / SUBGRID_SIZE * SUBGRID_SIZE + x / SUBGRID_SIZE
+}
+
+impl Grid {
+    /// Starts from the givens, or fails when two of them clash.
+    fn new(grid: &[[u8; SIZE]; SIZE]) -> Option<Self> {
+        let mut search = Self {
+            cells: [0; CELLS],
+            rows: [0; SIZE],
+            cols: [0; SIZE],
+            boxes: [0; SIZE],
+            trail: Vec::with_capacity(CELLS),
+        };
+        for (cell, &value) in grid.iter().flatten().enumerate() {
+            if value == 0 {
+                continue;
+            }
+            if search.candidates(cell) & (1 << value) == 0 {
+                return None;
+            }
+            search.place(cell, value);
+        }
+        search.trail.clear();
+        Some(search)
+    }
+
+    fn candidates(&self, cell: usize) -> u16 {
+        let used = self.rows[cell / SIZE] | self.cols[cell % SIZE] | self.boxes[box_of(cell)];
+        !used & ALL_DIGITS
+    }
+
+    fn place(&mut self, cell: usize, value: u8) {
+        let bit = 1 << value;
+        self.cells[cell] = value;
+        self.rows[cell / SIZE] |= bit;
+        self.cols[cell % SIZE] |= bit;
+        self.boxes[box_of(cell)] |= bit;
+        self.trail.push(cell as u8);
+    }
+
+    /// Empties the cells filled after the trail was `len` long.
+    fn undo(&mut self, len: usize) {
+        for cell in self.trail.drain(len..) {
+            let cell = cell as usize;
+            let bit = !(1 << self.cells[cell]);
+            self.cells[cell] = 0;
+            self.rows[cell / SIZE] &= bit;
+            self.cols[cell % SIZE] &= bit;
+            self.boxes[box_of(cell)] &= bit;
+        }
+    }
+
+    /// Fills in naked and hidden singles until there are none left,
+    /// returning `false` when a cell or a digit runs out of places.
+    fn propagate(&mut self) -> bool {
+        loop {
+            let mut progress = false;
+
+            for cell in 0..CELLS {
+                if self.cells[cell] != 0 {
+                    continue;
+                }
+                let candidates = self.candidates(cell);
+                match candidates.count_ones() {
+                    0 => return false,
+                    1 => {
+                        self.place(cell, candidates.trailing_zeros() as u8);
+                        progress = true;
+                    }
+                    _ => {}
+                }
+            }
+
+            for house in &HOUSES {
+                // Digits with at least one and with at least two places.
+                let (mut once, mut twice, mut placed) = (0u16, 0u16, 0u16);
+                for &cell in house {
+                    let cell = cell as usize;
+                    match self.cells[cell] {
+                        0 => {
+                            let candidates = self.candidates(cell);
+                            twice |= once & candidates;
+                            once |= candidates;
+                        }
+                        value => placed |= 1 << value,
+                    }
+                }
+                if (once | placed) != ALL_DIGITS {
+                    return false;
+                }
+
+                let mut singles = once & !twice & !placed;
+                while singles != 0 {
+                    let value = singles.trailing_zeros() as u8;
+                    singles &= singles - 1;
+                    let cell = house.iter().map(|&cell| cell as usize).find(|&cell| {
+                        self.cells[cell] == 0 && self.candidates(cell) & (1 << value) != 0
+                    });
+                    // Another single of the house may have taken the cell.
+                    let Some(cell) = cell else {
+                        return false;
+                    };
+                    self.place(cell, value);
+                    progress = true;
+                }
+            }
+
+            if !progress {
+                return true;
+            }
+        }
+    }
+
+    /// The empty cell with the fewest candidates, and its candidates.
+    fn most_constrained(&self) -> Option<(usize, u16)> {
+        let mut best: Option<(usize, u16)> = None;
+        for cell in (0..CELLS).filter(|&cell| self.cells[cell] == 0) {
+            let candidates = self.candidates(cell);
+            if best.is_none_or(|(_, best)| candidates.count_ones() < best.count_ones()) {
+                best = Some((cell, candidates));
+                if candidates.count_ones() <= 2 {
+                    break;
+                }
+            }
+        }
+        best
+    }
+
+    fn solution(&self) -> [[u8; SIZE]; SIZE] {
+        std::array::from_fn(|y| std::array::from_fn(|x| self.cells[y * SIZE + x]))
+    }
+
+    fn search(&mut self, visit: Visit) -> ControlFlow<()> {
+        let len = self.trail.len();
+        let flow = if !self.propagate() {
+            ControlFlow::Continue(())
+        } else if let Some((cell, mut candidates)) = self.most_constrained() {
+            let mut flow = ControlFlow::Continue(());
+            while candidates != 0 && flow.is_continue() {
+                let value = candidates.trailing_zeros() as u8;
+                candidates &= candidates - 1;
+                let guess = self.trail.len();
+                self.place(cell, value);
+                flow = self.search(visit);
+                self.undo(guess);
+            }
+            flow
+        } else {
+            visit(&self.solution())
+        };
+        self.undo(len);
+        flow
+    }
+}
+
+/// Backtracking on the cell with the fewest candidates, filling in naked
+/// and hidden singles after every guess.
+pub struct Backtracking;
+
+impl Solver for Backtracking {
+    fn solutions(&self, grid: &[[u8; SIZE]; SIZE], visit: Visit) {
+        if let Some(mut search) = Grid::new(grid) {
+            let _ = search.search(visit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::Board;
+
+    const SOLVED: &str =
+        "823675941497281653516349728739456812682713495154892367375164289948527136261938574";
+    const HARD: &str = include_str!("../benches/puzzles/hard.txt");
+    const SEVENTEEN_CLUE: &str = include_str!("../benches/puzzles/17-clue.txt");
+
+    fn grid(puzzle: &str) -> [[u8; SIZE]; SIZE] {
+        *puzzle.parse::<Board>().unwrap().grid()
+    }
+
+    fn is_solution_of(solution: &[[u8; SIZE]; SIZE], puzzle: &[[u8; SIZE]; SIZE]) -> bool {
+        let houses_complete = HOUSES.iter().all(|house| {
+            let digits = house.iter().fold(0u16, |mask, &cell| {
+                mask | 1 << solution[cell as usize / SIZE][cell as usize % SIZE]
+            });
+            digits == ALL_DIGITS
+        });
+        let keeps_givens = (0..SIZE)
+            .flat_map(|y| (0..SIZE).map(move |x| (x, y)))
+            .all(|(x, y)| puzzle[y][x] == 0 || puzzle[y][x] == solution[y][x]);
+        houses_complete && keeps_givens
+    }
+
+    /// Empties two pairs of cells that can swap their digits, a pattern that
+    /// always leaves exactly two solutions.
+    fn two_solutions() -> [[u8; SIZE]; SIZE] {
+        let mut grid = grid(SOLVED);
+        for (y1, y2) in [(0, 1), (0, 2), (1, 2)] {
+            for x1 in 0..SIZE {
+                for x2 in (x1 + 1)..SIZE {
+                    let (a, b) = (grid[y1][x1], grid[y1][x2]);
+                    if x1 / SUBGRID_SIZE != x2 / SUBGRID_SIZE
+                        && grid[y2][x1] == b
+                        && grid[y2][x2] == a
+                    {
+                        for (x, y) in [(x1, y1), (x2, y1), (x1, y2), (x2, y2)] {
+                            grid[y][x] = 0;
+                        }
+                        return grid;
+                    }
+                }
+            }
+        }
+        panic!("no swappable rectangle in the solved grid");
+    }
+
+    #[test]
+    fn solves_the_corpus() {
+        for puzzle in HARD.lines().chain(SEVENTEEN_CLUE.lines()) {
+            let puzzle = grid(puzzle);
+            let solutions = Backtracking.all(&puzzle);
+            assert_eq!(solutions.len(), 1, "{puzzle:?}");
+            assert!(is_solution_of(&solutions[0], &puzzle));
+        }
+    }
+
+    #[test]
+    fn leaves_full_grids_alone() {
+        let solved = grid(SOLVED);
+        assert_eq!(Backtracking.all(&solved), vec![solved]);
+    }
+
+    #[test]
+    fn counts_several_solutions() {
+        let puzzle = two_solutions();
+        let solutions = Backtracking.all(&puzzle);
+        assert_eq!(solutions.len(), 2);
+        assert_ne!(solutions[0], solutions[1]);
+        assert!(solutions.iter().all(|s| is_solution_of(s, &puzzle)));
+    }
+
+    #[test]
+    fn stops_at_the_limit() {
+        let empty = [[0; SIZE]; SIZE];
+        assert_eq!(Backtracking.count(&empty, 1), 1);
+        assert_eq!(Backtracking.count(&empty, 5), 5);
+        assert_eq!(Backtracking.count(&two_solutions(), 1), 1);
+        assert_eq!(Backtracking.count(&two_solutions(), 10), 2);
+
+        let mut visited = 0;
+        Backtracking.solutions(&empty, &mut |solution| {
+            assert!(is_solution_of(solution, &empty));
+            visited += 1;
+            ControlFlow::Break(())
+        });
+        assert_eq!(visited, 1);
+    }
+
+    #[test]
+    fn rejects_clashing_givens() {
+        for (x, y) in [(8, 0), (0, 8), (2, 2)] {
+            let mut puzzle = [[0; SIZE]; SIZE];
+            puzzle[0][0] = 5;
+            puzzle[y][x] = 5;
+            assert!(Grid::new(&puzzle).is_none());
+            assert_eq!(Backtracking.count(&puzzle, 2), 0);
+        }
+    }
+
+    #[test]
+    fn finds_cells_without_candidates() {
+        // 1 to 8 across the top row, and the 9 in the same box as its end.
+        let mut puzzle = [[0; SIZE]; SIZE];
+        puzzle[0][..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        puzzle[1][7] = 9;
+        assert!(!Grid::new(&puzzle).unwrap().propagate());
+        assert_eq!(Backtracking.count(&puzzle, 2), 0);
+    }
+
+    #[test]
+    fn finds_digits_without_places() {
+        // Every cell of the top row can still hold something, but the 1s
+        // below and beside it leave no room for a 1.
+        let mut puzzle = [[0; SIZE]; SIZE];
+        puzzle[1][1] = 1;
+        puzzle[2][4] = 1;
+        puzzle[0][6] = 2;
+        puzzle[0][7] = 3;
+        puzzle[3][8] = 1;
+        let mut search = Grid::new(&puzzle).unwrap();
+        let top_row = &HOUSES[0];
+        assert!(top_row.iter().all(|&cell| {
+            search.cells[cell as usize] != 0 || search.candidates(cell as usize) != 0
+        }));
+        assert!(!search.propagate());
+        assert_eq!(Backtracking.count(&puzzle, 2), 0);
+    }
+}
@@ -0,0 +1,1014 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::sudoku::{Difficulty, SIZE, SUBGRID_SIZE};
+
+/// Board coordinates as `(x, y)`, i.e. column then row.
+pub type Pos = (usize, usize);
+
+/// Bitmask with a bit set for every digit from 1 to 9.
+const ALL_DIGITS: u16 = 0b11_1111_1110;
+
+/// Score added to a rating when the solver gets stuck and the rest of the
+/// puzzle can only be solved by trial and error.
+const GUESS_WEIGHT: u32 = 100;
+
+/// A deduction the logical solver knows, from easiest to hardest.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Technique {
+    /// A digit with only one place left in a house.
+    HiddenSingle,
+    /// A cell with only one candidate left.
+    NakedSingle,
+    /// A digit confined to one line within a box.
+    Pointing,
+    /// A digit confined to one box within a line.
+    Claiming,
+    /// Two cells of a house with the same two candidates.
+    NakedPair,
+    /// Two digits with the same two places in a house.
+    HiddenPair,
+    /// Three cells of a house with three candidates between them.
+    NakedTriple,
+    /// Three digits with three places between them in a house.
+    HiddenTriple,
+    /// A digit confined to the same two columns in two rows, or the
+    /// other way round.
+    XWing,
+    /// An X-Wing over three rows or columns.
+    Swordfish,
+    /// A pivot cell and two pincers that rule a digit out where they meet.
+    XYWing,
+    /// Two-colouring the conjugate pairs of a digit.
+    SimpleColoring,
+}
+
+impl Technique {
+    /// Every technique, from the easiest to the hardest. The solver always
+    /// tries them in this order.
+    pub const ALL: [Technique; 12] = [
+        Self::HiddenSingle,
+        Self::NakedSingle,
+        Self::Pointing,
+        Self::Claiming,
+        Self::NakedPair,
+        Self::HiddenPair,
+        Self::NakedTriple,
+        Self::HiddenTriple,
+        Self::XWing,
+        Self::Swordfish,
+        Self::XYWing,
+        Self::SimpleColoring,
+    ];
+
+    /// Name shown to players.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::HiddenSingle => "Hidden single",
+            Self::NakedSingle => "Naked single",
+            Self::Pointing => "Pointing",
+            Self::Claiming => "Claiming",
+            Self::NakedPair => "Naked pair",
+            Self::HiddenPair => "Hidden pair",
+            Self::NakedTriple => "Naked triple",
+            Self::HiddenTriple => "Hidden triple",
+            Self::XWing => "X-Wing",
+            Self::Swordfish => "Swordfish",
+            Self::XYWing => "XY-Wing",
+            Self::SimpleColoring => "Simple coloring",
+        }
+    }
+
+    /// How much a single use of the technique adds to a puzzle's score.
+    pub fn weight(&self) -> u32 {
+        match self {
+            Self::HiddenSingle => 1,
+            Self::NakedSingle => 2,
+            Self::Pointing => 5,
+            Self::Claiming => 5,
+            Self::NakedPair => 10,
+            Self::HiddenPair => 12,
+            Self::NakedTriple => 20,
+            Self::HiddenTriple => 25,
+            Self::XWing => 30,
+            Self::Swordfish => 40,
+            Self::XYWing => 50,
+            Self::SimpleColoring => 60,
+        }
+    }
+}
+
+/// How hard a puzzle is to solve by logic alone.
+#[derive(Clone, Copy, Default)]
+pub struct Rating {
+    /// Sum of the weights of every step needed to solve the puzzle.
+    pub score: u32,
+    /// Hardest technique used, if any step was needed at all.
+    pub hardest: Option<Technique>,
+    /// Whether the known techniques were enough to solve the puzzle.
+    pub solved: bool,
+}
+
+impl Rating {
+    /// The difficulty a puzzle with this rating is sold as.
+    pub fn difficulty(&self) -> Difficulty {
+        match self.hardest {
+            _ if !self.solved => Difficulty::Expert,
+            None | Some(Technique::HiddenSingle | Technique::NakedSingle) => Difficulty::Easy,
+            Some(
+                Technique::Pointing
+                | Technique::Claiming
+                | Technique::NakedPair
+                | Technique::HiddenPair,
+            ) => Difficulty::Medium,
+            Some(_) => Difficulty::Hard,
+        }
+    }
+
+    /// Name of the hardest technique needed.
+    pub fn as_str(&self) -> &'static str {
+        match self.hardest {
+            _ if !self.solved => "Trial & error",
+            Some(technique) => technique.as_str(),
+            None => "Solved",
+        }
+    }
+}
+
+/// Rates a puzzle by solving it step by step with [`Solver`].
+pub fn rate(grid: &[[u8; SIZE]; SIZE]) -> Rating {
+    let mut solver = Solver::new(grid);
+    let steps = solver.solve();
+    let solved = solver.is_solved();
+    let score = steps
+        .iter()
+        .map(|step| step.technique.weight())
+        .sum::<u32>()
+        + if solved { 0 } else { GUESS_WEIGHT };
+
+    Rating {
+        score,
+        hardest: steps.iter().map(|step| step.technique).max(),
+        solved,
+    }
+}
+
+/// A row, column or box, indexed from 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum House {
+    /// A row, from the top.
+    Row(usize),
+    /// A column, from the left.
+    Col(usize),
+    /// A box, left to right, then top to bottom.
+    Box(usize),
+}
+
+impl House {
+    /// The cells of the house, in reading order.
+    pub fn cells(&self) -> [Pos; SIZE] {
+        std::array::from_fn(|i| match *self {
+            Self::Row(y) => (i, y),
+            Self::Col(x) => (x, i),
+            Self::Box(b) => (
+                b % SUBGRID_SIZE * SUBGRID_SIZE + i % SUBGRID_SIZE,
+                b / SUBGRID_SIZE * SUBGRID_SIZE + i / SUBGRID_SIZE,
+            ),
+        })
+    }
+
+    fn all() -> impl Iterator<Item = House> {
+        (0..SIZE)
+            .map(Self::Row)
+            .chain((0..SIZE).map(Self::Col))
+            .chain((0..SIZE).map(Self::Box))
+    }
+
+    fn lines() -> impl Iterator<Item = House> {
+        (0..SIZE).map(Self::Row).chain((0..SIZE).map(Self::Col))
+    }
+}
+
+impl fmt::Display for House {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Row(y) => write!(f, "row {}", y + 1),
+            Self::Col(x) => write!(f, "column {}", x + 1),
+            Self::Box(b) => write!(f, "box {}", b + 1),
+        }
+    }
+}
+
+/// A single logical deduction: the technique used, the cells and houses that
+/// make up the pattern, and what it places or eliminates.
+#[derive(Clone, Debug)]
+pub struct Step {
+    /// The technique that found the step.
+    pub technique: Technique,
+    /// Cells the deduction is based on.
+    pub cells: Vec<Pos>,
+    /// Houses the deduction is based on.
+    pub houses: Vec<House>,
+    /// Digits the deduction is about.
+    pub digits: Vec<u8>,
+    /// Digits that can be placed.
+    pub placements: Vec<(Pos, u8)>,
+    /// Candidates that can be removed.
+    pub eliminations: Vec<(Pos, u8)>,
+}
+
+impl Step {
+    fn new(technique: Technique) -> Self {
+        Self {
+            technique,
+            cells: Vec::new(),
+            houses: Vec::new(),
+            digits: Vec::new(),
+            placements: Vec::new(),
+            eliminations: Vec::new(),
+        }
+    }
+
+    fn placement(technique: Technique, pos: Pos, value: u8) -> Self {
+        Self {
+            cells: vec![pos],
+            digits: vec![value],
+            placements: vec![(pos, value)],
+            ..Self::new(technique)
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.technique.as_str())?;
+        let digits = join(self.digits.iter().map(u8::to_string), "/");
+        let cells = join(self.cells.iter().map(|&pos| cell_name(pos)), ", ");
+        let houses = join(self.houses.iter().map(House::to_string), " and ");
+        let mut removed: Vec<String> = self
+            .eliminations
+            .iter()
+            .map(|&(pos, _)| cell_name(pos))
+            .collect();
+        removed.dedup();
+        let removed = removed.join(", ");
+
+        match (self.technique, &self.houses[..], &self.placements[..]) {
+            (Technique::HiddenSingle, [house], &[(pos, value)]) => {
+                write!(f, "{value} in {house} can only go in {}", cell_name(pos))
+            }
+            (Technique::NakedSingle, _, &[(pos, value)]) => {
+                write!(f, "{} can only be {value}", cell_name(pos))
+            }
+            (Technique::Pointing | Technique::Claiming, &[source, target], _) => write!(
+                f,
+                "{digits} in {source} can only go in {target}, \
+                 so it can be removed from {removed}"
+            ),
+            (Technique::NakedPair | Technique::NakedTriple, _, _) => write!(
+                f,
+                "{cells} in {houses} can only hold {digits}, \
+                 so those can be removed from {removed}"
+            ),
+            (Technique::HiddenPair | Technique::HiddenTriple, _, _) => write!(
+                f,
+                "{digits} in {houses} can only go in {cells}, \
+                 so every other candidate can be removed from them"
+            ),
+            (Technique::XWing | Technique::Swordfish, houses, _) => {
+                let (base, cover) = houses.split_at(houses.len() / 2);
+                let base = join(base.iter().map(House::to_string), " and ");
+                let cover = join(cover.iter().map(House::to_string), " and ");
+                write!(
+                    f,
+                    "{digits} in {base} can only go in {cover}, \
+                     so it can be removed from {removed}"
+                )
+            }
+            (Technique::XYWing, _, _) => write!(
+                f,
+                "{} sees {} and {}, so one of them must be {}, \
+                 and it can be removed from {removed}",
+                cell_name(self.cells[0]),
+                cell_name(self.cells[1]),
+                cell_name(self.cells[2]),
+                self.eliminations[0].1
+            ),
+            _ => write!(
+                f,
+                "a chain of {digits}s through {cells} means it can be removed from {removed}"
+            ),
+        }
+    }
+}
+
+/// Solves a grid the way a person would, one named technique at a time,
+/// keeping track of the remaining candidates of every empty cell.
+#[derive(Clone)]
+pub struct Solver {
+    grid: [[u8; SIZE]; SIZE],
+    candidates: [[u16; SIZE]; SIZE],
+}
+
+impl Solver {
+    /// Starts from the givens in `grid`, with 0 for empty cells.
+    pub fn new(grid: &[[u8; SIZE]; SIZE]) -> Self {
+        let mut solver = Self {
+            grid: [[0; SIZE]; SIZE],
+            candidates: [[ALL_DIGITS; SIZE]; SIZE],
+        };
+        for (x, y) in positions() {
+            if grid[y][x] != 0 {
+                solver.place((x, y), grid[y][x]);
+            }
+        }
+        solver
+    }
+
+    /// Whether every cell has been filled.
+    pub fn is_solved(&self) -> bool {
+        self.grid.iter().flatten().all(|&value| value != 0)
+    }
+
+    /// Finds the easiest deduction available without applying it.
+    pub fn next_step(&self) -> Option<Step> {
+        Technique::ALL.iter().find_map(|&t| self.find(t))
+    }
+
+    /// Finds and applies the easiest deduction available.
+    pub fn step(&mut self) -> Option<Step> {
+        let step = self.next_step()?;
+        self.apply(&step);
+        Some(step)
+    }
+
+    /// Applies deductions until the grid is solved or no technique applies,
+    /// returning every step taken.
+    pub fn solve(&mut self) -> Vec<Step> {
+        std::iter::from_fn(|| self.step()).collect()
+    }
+
+    /// Places the digits and removes the candidates of a step.
+    pub fn apply(&mut self, step: &Step) {
+        for &(pos, value) in &step.placements {
+            self.place(pos, value);
+        }
+        for &(pos, value) in &step.eliminations {
+            self.eliminate(pos, value);
+        }
+    }
+
+    /// Removes a candidate ruled out earlier, such as by a step applied to
+    /// another board.
+    pub fn eliminate(&mut self, (x, y): Pos, value: u8) {
+        self.candidates[y][x] &= !bit(value);
+    }
+
+    /// The first step `technique` finds, if any.
+    pub fn find(&self, technique: Technique) -> Option<Step> {
+        match technique {
+            Technique::HiddenSingle => self.hidden_single(),
+            Technique::NakedSingle => self.naked_single(),
+            Technique::Pointing => self.pointing(),
+            Technique::Claiming => self.claiming(),
+            Technique::NakedPair => self.naked_subset(2, technique),
+            Technique::HiddenPair => self.hidden_subset(2, technique),
+            Technique::NakedTriple => self.naked_subset(3, technique),
+            Technique::HiddenTriple => self.hidden_subset(3, technique),
+            Technique::XWing => self.fish(2, technique),
+            Technique::Swordfish => self.fish(3, technique),
+            Technique::XYWing => self.xy_wing(),
+            Technique::SimpleColoring => self.simple_coloring(),
+        }
+    }
+
+    fn place(&mut self, (x, y): Pos, value: u8) {
+        self.grid[y][x] = value;
+        self.candidates[y][x] = 0;
+        for (px, py) in positions().filter(|&p| sees(p, (x, y))) {
+            self.candidates[py][px] &= !bit(value);
+        }
+    }
+
+    fn has(&self, (x, y): Pos, value: u8) -> bool {
+        self.candidates[y][x] & bit(value) != 0
+    }
+
+    fn positions_of(&self, house: House, value: u8) -> Vec<Pos> {
+        house
+            .cells()
+            .into_iter()
+            .filter(|&pos| self.has(pos, value))
+            .collect()
+    }
+
+    fn naked_single(&self) -> Option<Step> {
+        positions().find_map(|(x, y)| {
+            let mask = self.candidates[y][x];
+            (mask.count_ones() == 1)
+                .then(|| Step::placement(Technique::NakedSingle, (x, y), first_digit(mask)))
+        })
+    }
+
+    fn hidden_single(&self) -> Option<Step> {
+        House::all().find_map(|house| {
+            (1..=9).find_map(|value| match self.positions_of(house, value)[..] {
+                [pos] => Some(Step {
+                    houses: vec![house],
+                    ..Step::placement(Technique::HiddenSingle, pos, value)
+                }),
+                _ => None,
+            })
+        })
+    }
+
+    /// A digit confined to one row or column inside a box can be removed from
+    /// the rest of that line.
+    fn pointing(&self) -> Option<Step> {
+        (0..SIZE).find_map(|b| {
+            let house = House::Box(b);
+            (1..=9).find_map(|value| {
+                let cells = self.positions_of(house, value);
+                let line = shared_line(&cells)?;
+                self.confined(Technique::Pointing, value, cells, house, line)
+            })
+        })
+    }
+
+    /// A digit confined to one box inside a row or column can be removed from
+    /// the rest of that box.
+    fn claiming(&self) -> Option<Step> {
+        House::lines().find_map(|line| {
+            (1..=9).find_map(|value| {
+                let cells = self.positions_of(line, value);
+                let house = shared_box(&cells)?;
+                self.confined(Technique::Claiming, value, cells, line, house)
+            })
+        })
+    }
+
+    fn confined(
+        &self,
+        technique: Technique,
+        value: u8,
+        cells: Vec<Pos>,
+        source: House,
+        target: House,
+    ) -> Option<Step> {
+        let eliminations: Vec<_> = target
+            .cells()
+            .into_iter()
+            .filter(|pos| !cells.contains(pos) && self.has(*pos, value))
+            .map(|pos| (pos, value))
+            .collect();
+        if eliminations.is_empty() {
+            return None;
+        }
+        Some(Step {
+            cells,
+            houses: vec![source, target],
+            digits: vec![value],
+            eliminations,
+            ..Step::new(technique)
+        })
+    }
+
+    /// `size` cells of a house whose candidates span exactly `size` digits.
+    fn naked_subset(&self, size: usize, technique: Technique) -> Option<Step> {
+        House::all().find_map(|house| {
+            let cells = house.cells();
+            let open: Vec<Pos> = cells
+                .into_iter()
+                .filter(|&(x, y)| (2..=size as u32).contains(&self.candidates[y][x].count_ones()))
+                .collect();
+
+            combinations(&open, size).into_iter().find_map(|subset| {
+                let mask = subset
+                    .iter()
+                    .fold(0, |mask, &(x, y)| mask | self.candidates[y][x]);
+                if mask.count_ones() as usize != size {
+                    return None;
+                }
+                let eliminations: Vec<_> = cells
+                    .into_iter()
+                    .filter(|pos| !subset.contains(pos))
+                    .flat_map(|pos| digits(mask).map(move |value| (pos, value)))
+                    .filter(|&(pos, value)| self.has(pos, value))
+                    .collect();
+                if eliminations.is_empty() {
+                    return None;
+                }
+                Some(Step {
+                    cells: subset,
+                    houses: vec![house],
+                    digits: digits(mask).collect(),
+                    eliminations,
+                    ..Step::new(technique)
+                })
+            })
+        })
+    }
+
+    /// `size` digits of a house that can only go in the same `size` cells.
+    fn hidden_subset(&self, size: usize, technique: Technique) -> Option<Step> {
+        House::all().find_map(|house| {
+            let values: Vec<u8> = (1..=9)
+                .filter(|&value| (2..=size).contains(&self.positions_of(house, value).len()))
+                .collect();
+
+            combinations(&values, size).into_iter().find_map(|subset| {
+                let mut cells: Vec<Pos> = subset
+                    .iter()
+                    .flat_map(|&value| self.positions_of(house, value))
+                    .collect();
+                cells.sort_unstable_by_key(|&(x, y)| (y, x));
+                cells.dedup();
+                if cells.len() != size {
+                    return None;
+                }
+                let mask = subset.iter().fold(0, |mask, &value| mask | bit(value));
+                let eliminations: Vec<_> = cells
+                    .iter()
+                    .flat_map(|&(x, y)| {
+                        digits(self.candidates[y][x] & !mask).map(move |value| ((x, y), value))
+                    })
+                    .collect();
+                if eliminations.is_empty() {
+                    return None;
+                }
+                Some(Step {
+                    cells,
+                    houses: vec![house],
+                    digits: subset,
+                    eliminations,
+                    ..Step::new(technique)
+                })
+            })
+        })
+    }
+
+    /// X-Wing and Swordfish: a digit restricted to the same `size` columns in
+    /// `size` rows (or the other way around) can be removed from the rest of
+    /// those columns.
+    fn fish(&self, size: usize, technique: Technique) -> Option<Step> {
+        type Line = fn(usize) -> House;
+        let orientations: [(Line, Line); 2] = [(House::Row, House::Col), (House::Col, House::Row)];
+        (1..=9).find_map(|value| {
+            orientations.iter().find_map(|&(base, cover)| {
+                // For each base line, the indices along it where `value` fits.
+                let spots: Vec<(usize, u16)> = (0..SIZE)
+                    .filter_map(|line| {
+                        let mask = base(line)
+                            .cells()
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, &pos)| self.has(pos, value))
+                            .fold(0u16, |mask, (i, _)| mask | 1 << i);
+                        (2..=size as u32)
+                            .contains(&mask.count_ones())
+                            .then_some((line, mask))
+                    })
+                    .collect();
+
+                combinations(&spots, size).into_iter().find_map(|lines| {
+                    let mask = lines.iter().fold(0, |mask, &(_, m)| mask | m);
+                    if mask.count_ones() as usize != size {
+                        return None;
+                    }
+                    let base_lines: Vec<usize> = lines.iter().map(|&(line, _)| line).collect();
+                    let cover_lines: Vec<usize> =
+                        (0..SIZE).filter(|i| mask & 1 << i != 0).collect();
+                    let eliminations: Vec<_> = cover_lines
+                        .iter()
+                        .flat_map(|&i| {
+                            cover(i)
+                                .cells()
+                                .into_iter()
+                                .enumerate()
+                                .filter(|(j, _)| !base_lines.contains(j))
+                                .map(|(_, pos)| pos)
+                        })
+                        .filter(|&pos| self.has(pos, value))
+                        .map(|pos| (pos, value))
+                        .collect();
+                    if eliminations.is_empty() {
+                        return None;
+                    }
+                    let cells = base_lines
+                        .iter()
+                        .flat_map(|&line| self.positions_of(base(line), value))
+                        .collect();
+                    let houses = base_lines
+                        .iter()
+                        .map(|&i| base(i))
+                        .chain(cover_lines.iter().map(|&i| cover(i)))
+                        .collect();
+                    Some(Step {
+                        cells,
+                        houses,
+                        digits: vec![value],
+                        eliminations,
+                        ..Step::new(technique)
+                    })
+                })
+            })
+        })
+    }
+
+    /// A bivalue pivot `{a, b}` seeing pincers `{a, z}` and `{b, z}`: one of the
+    /// pincers must be `z`, so `z` goes from every cell that sees both.
+    fn xy_wing(&self) -> Option<Step> {
+        let bivalue: Vec<Pos> = positions()
+            .filter(|&(x, y)| self.candidates[y][x].count_ones() == 2)
+            .collect();
+        let mask = |(x, y): Pos| self.candidates[y][x];
+
+        bivalue.iter().find_map(|&pivot| {
+            let pm = mask(pivot);
+            bivalue
+                .iter()
+                .filter(|&&p| sees(pivot, p))
+                .filter(|&&p| (mask(p) & pm).count_ones() == 1)
+                .find_map(|&first| {
+                    let z = mask(first) & !pm;
+                    let other = pm & !mask(first);
+                    bivalue
+                        .iter()
+                        .filter(|&&p| p != first && sees(pivot, p))
+                        .filter(|&&p| mask(p) == other | z)
+                        .find_map(|&second| {
+                            let value = first_digit(z);
+                            let eliminations: Vec<_> = positions()
+                                .filter(|&p| p != pivot && sees(p, first) && sees(p, second))
+                                .filter(|&p| self.has(p, value))
+                                .map(|p| (p, value))
+                                .collect();
+                            if eliminations.is_empty() {
+                                return None;
+                            }
+                            Some(Step {
+                                cells: vec![pivot, first, second],
+                                digits: digits(pm | z).collect(),
+                                eliminations,
+                                ..Step::new(Technique::XYWing)
+                            })
+                        })
+                })
+        })
+    }
+
+    /// Colors chains of conjugate pairs of a digit in two alternating colors.
+    /// Two cells of the same color seeing each other rule that whole color
+    /// out, and any cell seeing both colors can't hold the digit.
+    fn simple_coloring(&self) -> Option<Step> {
+        (1..=9).find_map(|value| {
+            let links: Vec<(Pos, Pos)> = House::all()
+                .filter_map(|house| match self.positions_of(house, value)[..] {
+                    [a, b] => Some((a, b)),
+                    _ => None,
+                })
+                .collect();
+
+            let mut colored: Vec<Pos> = Vec::new();
+            let mut starts = links.iter().map(|&(a, _)| a);
+            std::iter::from_fn(|| {
+                let start = starts.find(|p| !colored.contains(p))?;
+                let chain = color_chain(&links, start);
+                colored.extend(chain[0].iter().chain(&chain[1]));
+                Some(chain)
+            })
+            .find_map(|[on, off]| self.coloring_step(value, on, off))
+        })
+    }
+
+    fn coloring_step(&self, value: u8, on: Vec<Pos>, off: Vec<Pos>) -> Option<Step> {
+        let clashes = |color: &[Pos]| color.iter().any(|&a| color.iter().any(|&b| sees(a, b)));
+
+        let eliminations: Vec<_> = if clashes(&on) {
+            on.iter().map(|&pos| (pos, value)).collect()
+        } else if clashes(&off) {
+            off.iter().map(|&pos| (pos, value)).collect()
+        } else {
+            positions()
+                .filter(|p| self.has(*p, value) && !on.contains(p) && !off.contains(p))
+                .filter(|&p| on.iter().any(|&a| sees(a, p)) && off.iter().any(|&b| sees(b, p)))
+                .map(|pos| (pos, value))
+                .collect()
+        };
+        if eliminations.is_empty() {
+            return None;
+        }
+        Some(Step {
+            cells: on.into_iter().chain(off).collect(),
+            digits: vec![value],
+            eliminations,
+            ..Step::new(Technique::SimpleColoring)
+        })
+    }
+}
+
+/// Splits the chain of conjugate links reachable from `start` into its two
+/// colors.
+fn color_chain(links: &[(Pos, Pos)], start: Pos) -> [Vec<Pos>; 2] {
+    let mut colors: [Vec<Pos>; 2] = [vec![start], Vec::new()];
+    let mut queue = vec![(start, 0)];
+    while let Some((pos, color)) = queue.pop() {
+        let neighbours = links.iter().filter_map(|&(a, b)| match pos {
+            _ if a == pos => Some(b),
+            _ if b == pos => Some(a),
+            _ => None,
+        });
+        for next in neighbours {
+            if colors.iter().any(|c| c.contains(&next)) {
+                continue;
+            }
+            colors[1 - color].push(next);
+            queue.push((next, 1 - color));
+        }
+    }
+    colors
+}
+
+fn shared_line(cells: &[Pos]) -> Option<House> {
+    let &[(x, y), ref rest @ ..] = cells else {
+        return None;
+    };
+    if rest.is_empty() {
+        return None;
+    }
+    if rest.iter().all(|&(_, ry)| ry == y) {
+        return Some(House::Row(y));
+    }
+    if rest.iter().all(|&(rx, _)| rx == x) {
+        return Some(House::Col(x));
+    }
+    None
+}
+
+fn shared_box(cells: &[Pos]) -> Option<House> {
+    let &[first, ref rest @ ..] = cells else {
+        return None;
+    };
+    let box_of = |(x, y): Pos| y / SUBGRID_SIZE * SUBGRID_SIZE + x / SUBGRID_SIZE;
+    (!rest.is_empty() && rest.iter().all(|&p| box_of(p) == box_of(first)))
+        .then(|| House::Box(box_of(first)))
+}
+
+fn combinations<T: Copy>(items: &[T], size: usize) -> Vec<Vec<T>> {
+    if size == 0 {
+        return vec![Vec::new()];
+    }
+    if items.len() < size {
+        return Vec::new();
+    }
+    let (&first, rest) = items.split_first().unwrap();
+    let mut with_first = combinations(rest, size - 1);
+    for combination in &mut with_first {
+        combination.insert(0, first);
+    }
+    with_first.extend(combinations(rest, size));
+    with_first
+}
+
+/// Formats a position the way it is usually written in solving guides, e.g.
+/// `r4c6` for the 6th cell in the 4th row.
+pub fn cell_name((x, y): Pos) -> String {
+    format!("r{}c{}", y + 1, x + 1)
+}
+
+fn join(items: impl Iterator<Item = String>, separator: &str) -> String {
+    items.collect::<Vec<_>>().join(separator)
+}
+
+fn positions() -> impl Iterator<Item = Pos> {
+    (0..SIZE).flat_map(|y| (0..SIZE).map(move |x| (x, y)))
+}
+
+/// Whether two distinct cells share a row, column or box.
+pub fn sees(a: Pos, b: Pos) -> bool {
+    let same_box =
+        a.0 / SUBGRID_SIZE == b.0 / SUBGRID_SIZE && a.1 / SUBGRID_SIZE == b.1 / SUBGRID_SIZE;
+    a != b && (a.0 == b.0 || a.1 == b.1 || same_box)
+}
+
+fn bit(value: u8) -> u16 {
+    1 << value
+}
+
+fn first_digit(mask: u16) -> u8 {
+    mask.trailing_zeros() as u8
+}
+
+/// Iterates over the digits set in a candidate bitmask.
+pub fn digits(mask: u16) -> impl Iterator<Item = u8> {
+    (1..=9).filter(move |&value| mask & bit(value) != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A solver for an empty grid, with every candidate still open.
+    fn open() -> Solver {
+        Solver::new(&[[0; SIZE]; SIZE])
+    }
+
+    fn set(solver: &mut Solver, (x, y): Pos, values: &[u8]) {
+        solver.candidates[y][x] = values.iter().fold(0, |mask, &value| mask | bit(value));
+    }
+
+    fn row(y: usize, xs: impl IntoIterator<Item = usize>) -> Vec<Pos> {
+        xs.into_iter().map(|x| (x, y)).collect()
+    }
+
+    fn with(cells: Vec<Pos>, values: &[u8]) -> Vec<(Pos, u8)> {
+        cells
+            .into_iter()
+            .flat_map(|pos| values.iter().map(move |&value| (pos, value)))
+            .collect()
+    }
+
+    fn assert_step(step: Option<Step>, cells: &[Pos], houses: &[House], digits: &[u8]) -> Step {
+        let step = step.expect("no step found");
+        assert_eq!(step.cells, cells);
+        assert_eq!(step.houses, houses);
+        assert_eq!(step.digits, digits);
+        step
+    }
+
+    #[test]
+    fn naked_single() {
+        let mut grid = [[0; SIZE]; SIZE];
+        grid[0][..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        let step = Solver::new(&grid).find(Technique::NakedSingle);
+        let step = assert_step(step, &[(8, 0)], &[], &[9]);
+        assert_eq!(step.placements, [((8, 0), 9)]);
+        assert!(step.eliminations.is_empty());
+    }
+
+    #[test]
+    fn hidden_single() {
+        // The 1s rule out every cell of the top row but the last.
+        let mut grid = [[0; SIZE]; SIZE];
+        grid[1][0] = 1;
+        grid[2][4] = 1;
+        grid[3][6] = 1;
+        grid[6][7] = 1;
+        let step = Solver::new(&grid).find(Technique::HiddenSingle);
+        let step = assert_step(step, &[(8, 0)], &[House::Row(0)], &[1]);
+        assert_eq!(step.placements, [((8, 0), 1)]);
+        assert!(step.eliminations.is_empty());
+    }
+
+    #[test]
+    fn pointing() {
+        let mut solver = open();
+        for pos in row(1, 0..3).into_iter().chain(row(2, 0..3)) {
+            solver.eliminate(pos, 5);
+        }
+        let step = solver.find(Technique::Pointing);
+        let step = assert_step(step, &row(0, 0..3), &[House::Box(0), House::Row(0)], &[5]);
+        assert_eq!(step.eliminations, with(row(0, 3..9), &[5]));
+        assert!(step.placements.is_empty());
+    }
+
+    #[test]
+    fn claiming() {
+        let mut solver = open();
+        for pos in row(0, 3..9) {
+            solver.eliminate(pos, 5);
+        }
+        let step = solver.find(Technique::Claiming);
+        let step = assert_step(step, &row(0, 0..3), &[House::Row(0), House::Box(0)], &[5]);
+        let rest_of_box = row(1, 0..3).into_iter().chain(row(2, 0..3)).collect();
+        assert_eq!(step.eliminations, with(rest_of_box, &[5]));
+    }
+
+    #[test]
+    fn naked_pair() {
+        let mut solver = open();
+        set(&mut solver, (0, 0), &[1, 2]);
+        set(&mut solver, (1, 0), &[1, 2]);
+        let step = solver.find(Technique::NakedPair);
+        let step = assert_step(step, &row(0, 0..2), &[House::Row(0)], &[1, 2]);
+        assert_eq!(step.eliminations, with(row(0, 2..9), &[1, 2]));
+    }
+
+    #[test]
+    fn naked_triple() {
+        let mut solver = open();
+        set(&mut solver, (0, 0), &[1, 2]);
+        set(&mut solver, (1, 0), &[2, 3]);
+        set(&mut solver, (2, 0), &[1, 3]);
+        let step = solver.find(Technique::NakedTriple);
+        let step = assert_step(step, &row(0, 0..3), &[House::Row(0)], &[1, 2, 3]);
+        assert_eq!(step.eliminations, with(row(0, 3..9), &[1, 2, 3]));
+    }
+
+    #[test]
+    fn hidden_pair() {
+        let mut solver = open();
+        for pos in row(0, 2..9) {
+            solver.eliminate(pos, 1);
+            solver.eliminate(pos, 2);
+        }
+        let step = solver.find(Technique::HiddenPair);
+        let step = assert_step(step, &row(0, 0..2), &[House::Row(0)], &[1, 2]);
+        assert_eq!(
+            step.eliminations,
+            with(row(0, 0..2), &[3, 4, 5, 6, 7, 8, 9])
+        );
+    }
+
+    #[test]
+    fn hidden_triple() {
+        let mut solver = open();
+        for pos in row(0, 3..9) {
+            for value in 1..=3 {
+                solver.eliminate(pos, value);
+            }
+        }
+        let step = solver.find(Technique::HiddenTriple);
+        let step = assert_step(step, &row(0, 0..3), &[House::Row(0)], &[1, 2, 3]);
+        assert_eq!(step.eliminations, with(row(0, 0..3), &[4, 5, 6, 7, 8, 9]));
+    }
+
+    #[test]
+    fn x_wing() {
+        let mut solver = open();
+        for y in [0, 4] {
+            for pos in row(y, [1, 2, 3, 4, 6, 7, 8]) {
+                solver.eliminate(pos, 1);
+            }
+        }
+        let step = solver.find(Technique::XWing);
+        let houses = [House::Row(0), House::Row(4), House::Col(0), House::Col(5)];
+        let step = assert_step(step, &[(0, 0), (5, 0), (0, 4), (5, 4)], &houses, &[1]);
+        let others = [1, 2, 3, 5, 6, 7, 8];
+        let eliminations: Vec<Pos> = [0, 5]
+            .into_iter()
+            .flat_map(|x| others.map(|y| (x, y)))
+            .collect();
+        assert_eq!(step.eliminations, with(eliminations, &[1]));
+    }
+
+    #[test]
+    fn swordfish() {
+        // Each row holds the 1 in two of the three columns, so no two rows
+        // make an X-Wing.
+        let mut solver = open();
+        for (y, columns) in [(0, [0, 3]), (3, [3, 6]), (6, [0, 6])] {
+            for x in (0..SIZE).filter(|x| !columns.contains(x)) {
+                solver.eliminate((x, y), 1);
+            }
+        }
+        assert!(solver.find(Technique::XWing).is_none());
+        let step = solver.find(Technique::Swordfish);
+        let houses = [0, 3, 6]
+            .map(House::Row)
+            .into_iter()
+            .chain([0, 3, 6].map(House::Col));
+        let cells = [(0, 0), (3, 0), (3, 3), (6, 3), (0, 6), (6, 6)];
+        let step = assert_step(step, &cells, &houses.collect::<Vec<_>>(), &[1]);
+        let others = [1, 2, 4, 5, 7, 8];
+        let eliminations: Vec<Pos> = [0, 3, 6]
+            .into_iter()
+            .flat_map(|x| others.map(|y| (x, y)))
+            .collect();
+        assert_eq!(step.eliminations, with(eliminations, &[1]));
+    }
+
+    #[test]
+    fn xy_wing() {
+        // Pivot {1, 2} with pincers {1, 3} along its row and {2, 3} in its
+        // box: whichever pincer isn't 3, the other one is.
+        let mut solver = open();
+        set(&mut solver, (0, 0), &[1, 2]);
+        set(&mut solver, (4, 0), &[1, 3]);
+        set(&mut solver, (1, 1), &[2, 3]);
+        let step = solver.find(Technique::XYWing);
+        let step = assert_step(step, &[(0, 0), (4, 0), (1, 1)], &[], &[1, 2, 3]);
+        let seeing_both = [(1, 0), (2, 0), (3, 1), (4, 1), (5, 1)];
+        assert_eq!(step.eliminations, with(seeing_both.to_vec(), &[3]));
+    }
+
+    #[test]
+    fn simple_coloring() {
+        // Row 1 and columns 1 and 5 each hold the 1 in two places, which
+        // chain r1c1 and r5c5 against r1c5 and r5c1. Either way one of them
+        // covers the rest of row 5.
+        let mut solver = open();
+        for pos in row(0, [1, 2, 3, 5, 6, 7, 8]) {
+            solver.eliminate(pos, 1);
+        }
+        for y in [1, 2, 3, 5, 6, 7, 8] {
+            solver.eliminate((0, y), 1);
+            solver.eliminate((4, y), 1);
+        }
+        let step = solver.find(Technique::SimpleColoring);
+        let cells = [(0, 0), (4, 4), (4, 0), (0, 4)];
+        let step = assert_step(step, &cells, &[], &[1]);
+        assert_eq!(step.eliminations, with(row(4, [1, 2, 3, 5, 6, 7, 8]), &[1]));
+    }
+}
@@ -0,0 +1,1277 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::{Datelike, NaiveDate};
+use rand::{prelude::SliceRandom, seq::IteratorRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    ops::ControlFlow,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    save::{Encoding, Save},
+    search::{self, Backtracking},
+    solver::{self, cell_name, House, Pos, Rating, Solver, Step},
+};
+
+/// Number of rows, columns and boxes, and of cells in each.
+pub const SIZE: usize = 9;
+/// Number of rows and columns of cells in a box.
+pub const SUBGRID_SIZE: usize = 3;
+
+/// How many checks and hints a game allows.
+#[derive(Clone, Copy)]
+pub struct Limits {
+    /// Cells that can be checked against the solution.
+    pub checks: u8,
+    /// Deductions that can be shown or applied as hints.
+    pub hints: u8,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            checks: 3,
+            hints: 3,
+        }
+    }
+}
+
+/// Difficulty of the daily puzzle.
+pub const DAILY_DIFFICULTY: Difficulty = Difficulty::Medium;
+
+/// Puzzles [`Sudoku::generate_seeded`] grades before settling for the one
+/// closest to the difficulty asked for.
+const MAX_ATTEMPTS: usize = 1000;
+
+/// A game in progress: the board, its solution, the move history and the
+/// clock.
+#[derive(Default)]
+pub struct Sudoku {
+    grid: [[Cell; SIZE]; SIZE],
+    solution: [[u8; SIZE]; SIZE],
+    state: GameState,
+    /// Every move made, including undone ones that can still be redone.
+    history: Vec<Move>,
+    /// How many moves of `history` are applied to `grid`.
+    position: usize,
+    /// Board states to roll back to after a guess, latest last.
+    bookmarks: Vec<Bookmark>,
+    /// Whether repeated digits are pointed out as they're entered.
+    show_conflicts: bool,
+    /// Whether `show_conflicts` was turned on at any point of the game.
+    assisted: bool,
+    /// Seed the puzzle was generated from, if it was.
+    seed: Option<u64>,
+    /// Date of the daily puzzle this game is.
+    daily: Option<NaiveDate>,
+    start: Option<Instant>,
+    elapsed: Duration,
+    difficulty: Difficulty,
+    rating: Rating,
+    limits: Limits,
+    checks: u8,
+    hints: u8,
+    /// Candidates ruled out by applied hints, by cell, which the board alone
+    /// doesn't show. Not saved: the solver finds them again after loading.
+    eliminated: [[u16; SIZE]; SIZE],
+}
+
+/// One undoable step, with every cell it changed.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Move {
+    /// What made the move.
+    pub kind: MoveKind,
+    changes: Vec<Change>,
+    /// The bookmark a rollback went back to, put back when it's undone.
+    bookmark: Option<Bookmark>,
+}
+
+/// What made a move.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum MoveKind {
+    /// A value or note typed by the player.
+    Edit,
+    /// A hint applied to the board.
+    Hint,
+    /// The board cleared of every entry.
+    ClearBoard,
+    /// The solution filled in for the player.
+    Solve,
+    /// Going back to a bookmark.
+    Rollback,
+}
+
+/// A board state saved before trying a guess.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Bookmark {
+    grid: [[Cell; SIZE]; SIZE],
+    /// Number of moves that had been made when it was placed.
+    pub position: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub(crate) struct Change {
+    x: usize,
+    y: usize,
+    old: Cell,
+    new: Cell,
+}
+
+/// A grid of digits, with 0 for empty cells, such as a puzzle or its
+/// solution.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Board {
+    grid: [[u8; SIZE]; SIZE],
+}
+
+/// A cell of a game, with its digit, pencil marks and check marks.
+#[derive(Serialize, Deserialize, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Cell {
+    /// The digit in the cell, or 0 when it's empty.
+    pub value: u8,
+    flags: u8,
+    notes: u16,
+}
+
+const CELL_CHECKED: u8 = 0b0001;
+const CELL_CORRECT: u8 = 0b0010;
+const CELL_WRITABLE: u8 = 0b0100;
+
+impl Cell {
+    /// A cell holding `value`, which is a given unless it's 0.
+    pub fn new(value: u8) -> Self {
+        let flags = if value == 0 { CELL_WRITABLE } else { 0 };
+        Self {
+            value,
+            flags,
+            notes: 0,
+        }
+    }
+
+    /// A cell from before notes were added, with its old flags.
+    pub(crate) fn without_notes(value: u8, flags: u8) -> Self {
+        Self {
+            value,
+            flags,
+            notes: 0,
+        }
+    }
+
+    /// Removes the check mark.
+    pub fn uncheck(&mut self) {
+        self.flags &= !CELL_CHECKED;
+        self.flags &= !CELL_CORRECT;
+    }
+
+    /// Marks the cell as checked against the solution.
+    pub fn check(&mut self, correct: bool) {
+        self.flags |= CELL_CHECKED;
+        if correct {
+            self.flags |= CELL_CORRECT;
+        }
+    }
+
+    /// Whether the player can change the cell, that is, it isn't a given.
+    pub fn writable(&self) -> bool {
+        self.flags & CELL_WRITABLE != 0
+    }
+
+    /// Whether the cell was checked since it last changed.
+    pub fn checked(&self) -> bool {
+        self.flags & CELL_CHECKED != 0
+    }
+
+    /// Whether the cell held the right digit when it was checked.
+    pub fn correct(&self) -> bool {
+        self.flags & CELL_CORRECT != 0
+    }
+
+    /// Whether `value` is pencilled in as a candidate for this cell.
+    pub fn has_note(&self, value: u8) -> bool {
+        self.notes & (1 << value) != 0
+    }
+
+    /// Whether any candidate is pencilled in.
+    pub fn has_notes(&self) -> bool {
+        self.notes != 0
+    }
+}
+
+/// What a hint points the player to.
+pub enum Hint {
+    /// The next logical deduction on the current board.
+    Step(Step),
+    /// A cell whose value doesn't match the solution.
+    Mistake(Pos),
+    /// The value of a cell, when no known technique applies.
+    Reveal(Pos, u8),
+}
+
+impl Hint {
+    /// Cells the hint is about.
+    pub fn cells(&self) -> Vec<Pos> {
+        match self {
+            Self::Step(step) => step.cells.clone(),
+            Self::Mistake(pos) | Self::Reveal(pos, _) => vec![*pos],
+        }
+    }
+
+    /// Rows, columns and boxes the hint is about.
+    pub fn houses(&self) -> &[House] {
+        match self {
+            Self::Step(step) => &step.houses,
+            _ => &[],
+        }
+    }
+
+    /// Candidates the hint rules out.
+    pub fn eliminations(&self) -> &[(Pos, u8)] {
+        match self {
+            Self::Step(step) => &step.eliminations,
+            _ => &[],
+        }
+    }
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Step(step) => write!(f, "{step}"),
+            Self::Mistake(pos) => write!(f, "Mistake: {} is wrong", cell_name(*pos)),
+            Self::Reveal(pos, value) => write!(
+                f,
+                "No known technique applies here, but {} is {value}",
+                cell_name(*pos)
+            ),
+        }
+    }
+}
+
+impl Move {
+    /// A move that removed no bookmark, as moves were saved before
+    /// rollbacks kept theirs.
+    pub(crate) fn without_bookmark(kind: MoveKind, changes: Vec<Change>) -> Self {
+        Self {
+            kind,
+            changes,
+            bookmark: None,
+        }
+    }
+
+    /// The cell the move is about, for moving the cursor to it.
+    fn pos(&self) -> (usize, usize) {
+        self.changes
+            .first()
+            .map_or((0, 0), |change| (change.x, change.y))
+    }
+}
+
+/// Describes a move as in "r3c5: 4 → 7".
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cells = match self.changes.len() {
+            1 => "1 cell".to_string(),
+            n => format!("{n} cells"),
+        };
+        match (self.kind, &self.changes[..]) {
+            (MoveKind::Edit, [change]) => write!(f, "{change}"),
+            (MoveKind::Hint, [change]) => write!(f, "Hint {change}"),
+            (MoveKind::Hint, _) => write!(f, "Hint: {cells}"),
+            (MoveKind::ClearBoard, _) => write!(f, "Clear: {cells}"),
+            (MoveKind::Solve, _) => write!(f, "Solve: {cells}"),
+            (MoveKind::Rollback, _) => write!(f, "Rollback: {cells}"),
+            (MoveKind::Edit, _) => write!(f, "Edit: {cells}"),
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", cell_name((self.x, self.y)))?;
+        let (old, new) = (self.old, self.new);
+        if old.value != new.value {
+            let digit = |value| {
+                if value == 0 {
+                    '.'
+                } else {
+                    (b'0' + value) as char
+                }
+            };
+            return write!(f, "{} → {}", digit(old.value), digit(new.value));
+        }
+
+        let added = new.notes & !old.notes;
+        let removed = old.notes & !new.notes;
+        match (solver::digits(added).next(), solver::digits(removed).next()) {
+            (Some(note), None) => write!(f, "note +{note}"),
+            (None, Some(note)) if new.has_notes() => write!(f, "note -{note}"),
+            (None, Some(_)) => write!(f, "notes cleared"),
+            _ => write!(f, "checked"),
+        }
+    }
+}
+
+/// Where a game is at.
+#[derive(Clone, Copy, Default)]
+pub enum GameState {
+    /// Being played.
+    #[default]
+    Running,
+    /// Paused, with the board hidden.
+    Paused,
+    /// Finished by revealing the solution.
+    Solved,
+    /// Finished by the player.
+    Won,
+}
+
+/// How hard a puzzle is, graded by the techniques needed to solve it.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    /// Only singles needed.
+    #[default]
+    Easy,
+    /// Needs locked candidates or subsets.
+    Medium,
+    /// Needs fish, wings or coloring.
+    Hard,
+    /// Needs guessing.
+    Expert,
+}
+
+impl Difficulty {
+    /// Name shown to players.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Easy => "Easy",
+            Self::Medium => "Medium",
+            Self::Hard => "Hard",
+            Self::Expert => "Expert",
+        }
+    }
+
+    /// Every difficulty, easiest first.
+    pub const ALL: [Difficulty; 4] = [Self::Easy, Self::Medium, Self::Hard, Self::Expert];
+
+    /// Cells to try emptying. Grading is left to the solver, but harder
+    /// puzzles turn up more often with fewer givens.
+    fn num_holes(&self, rng: &mut impl Rng) -> usize {
+        match self {
+            Difficulty::Easy => (45..50).choose(rng).unwrap(),
+            Difficulty::Medium => (50..56).choose(rng).unwrap(),
+            Difficulty::Hard => (56..61).choose(rng).unwrap(),
+            Difficulty::Expert => (61..65).choose(rng).unwrap(),
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| anyhow!("unknown difficulty '{s}'"))
+    }
+}
+
+impl Sudoku {
+    /// Generates puzzles until one is graded as `difficulty` by the logical
+    /// solver.
+    pub fn generate(difficulty: Difficulty) -> Self {
+        Self::generate_seeded(difficulty, rand::random())
+    }
+
+    /// Like [`Sudoku::generate`], but always builds the same puzzle for the
+    /// same difficulty and seed. Gives up after [`MAX_ATTEMPTS`] puzzles,
+    /// keeping the one graded closest to `difficulty`.
+    pub fn generate_seeded(difficulty: Difficulty, seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let distance = |rating: &Rating| (rating.difficulty() as i32 - difficulty as i32).abs();
+        let mut closest: Option<(Board, Board, Rating)> = None;
+        for _ in 0..MAX_ATTEMPTS {
+            let solution = Board::generate(&mut rng);
+            let puzzle = solution.generate_puzzle(difficulty.num_holes(&mut rng), &mut rng);
+            let rating = solver::rate(&puzzle.grid);
+            if closest
+                .as_ref()
+                .is_none_or(|(_, _, best)| distance(&rating) < distance(best))
+            {
+                let found = distance(&rating) == 0;
+                closest = Some((puzzle, solution, rating));
+                if found {
+                    break;
+                }
+            }
+        }
+        let (puzzle, solution, rating) = closest.expect("at least one attempt");
+        let mut sudoku = Self::new(puzzle, solution, rating);
+        sudoku.seed = Some(seed);
+        sudoku
+    }
+
+    /// The puzzle of the day, the same for everyone on that date.
+    pub fn daily(date: NaiveDate) -> Self {
+        let seed = date.year() as u64 * 10000 + date.month() as u64 * 100 + date.day() as u64;
+        let mut sudoku = Self::generate_seeded(DAILY_DIFFICULTY, seed);
+        sudoku.daily = Some(date);
+        sudoku
+    }
+
+    /// Starts a game from an existing puzzle, which must have exactly one
+    /// solution.
+    pub fn from_puzzle(puzzle: Board) -> Result<Self> {
+        if puzzle.has_conflicts() {
+            bail!("puzzle breaks the rules");
+        }
+        match puzzle.clone().count_solutions(2) {
+            0 => bail!("puzzle has no solution"),
+            1 => {}
+            _ => bail!("puzzle has more than one solution"),
+        }
+
+        let mut solution = puzzle.clone();
+        solution.solve();
+        let rating = solver::rate(&puzzle.grid);
+        Ok(Self::new(puzzle, solution, rating))
+    }
+
+    fn new(puzzle: Board, solution: Board, rating: Rating) -> Self {
+        Self {
+            difficulty: rating.difficulty(),
+            rating,
+            start: Some(Instant::now()),
+            grid: puzzle.grid.map(|row| row.map(Cell::new)),
+            solution: solution.grid,
+            ..Default::default()
+        }
+    }
+
+    /// Reads a save written by any version of [`Sudoku::save`].
+    pub fn load(bytes: &[u8]) -> Result<Self> {
+        Save::decode(bytes).map(Self::from_save)
+    }
+
+    fn from_save(save: Save) -> Self {
+        let mut sudoku = Self {
+            hints: save.hints,
+            checks: save.checks,
+            difficulty: save.difficulty,
+            start: Some(Instant::now()),
+            elapsed: Duration::from_secs(save.elapsed),
+            grid: save.grid,
+            solution: save.solution,
+            position: save.position.min(save.history.len()),
+            history: save.history,
+            bookmarks: save.bookmarks,
+            show_conflicts: save.show_conflicts,
+            assisted: save.assisted,
+            seed: save.seed,
+            daily: save.daily,
+            ..Default::default()
+        };
+        sudoku.rating = solver::rate(&sudoku.puzzle().grid);
+        sudoku
+    }
+
+    /// Encodes the game in the current save layout.
+    pub fn save(&self, encoding: Encoding) -> Result<Vec<u8>> {
+        let save = Save {
+            grid: self.grid,
+            solution: self.solution,
+            difficulty: self.difficulty,
+            elapsed: self.elapsed().as_secs(),
+            checks: self.checks,
+            hints: self.hints,
+            history: self.history.clone(),
+            position: self.position,
+            bookmarks: self.bookmarks.clone(),
+            show_conflicts: self.show_conflicts,
+            assisted: self.assisted,
+            seed: self.seed,
+            daily: self.daily,
+        };
+        save.encode(encoding)
+    }
+
+    /// The cells as they are now.
+    pub fn grid(&self) -> &[[Cell; SIZE]; SIZE] {
+        &self.grid
+    }
+
+    /// The board as it was handed to the player, without their entries.
+    pub fn puzzle(&self) -> Board {
+        Board {
+            grid: self
+                .grid
+                .map(|row| row.map(|cell| if cell.writable() { 0 } else { cell.value })),
+        }
+    }
+
+    /// The difficulty the puzzle was rated as.
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    /// How many checks and hints are allowed.
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Changes how many checks and hints are allowed.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// The date of the daily puzzle, when this game is one.
+    pub fn daily_date(&self) -> Option<NaiveDate> {
+        self.daily
+    }
+
+    /// The seed the puzzle was generated from, to build it again with
+    /// [`Sudoku::generate_seeded`].
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// How hard the puzzle is.
+    pub fn rating(&self) -> Rating {
+        self.rating
+    }
+
+    /// Number of hints used.
+    pub fn hints(&self) -> u8 {
+        self.hints
+    }
+
+    /// Number of checks used.
+    pub fn checks(&self) -> u8 {
+        self.checks
+    }
+
+    /// Where the game is at.
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    /// Whether the game is paused.
+    pub fn is_paused(&self) -> bool {
+        matches!(self.state, GameState::Paused)
+    }
+
+    /// Whether the game is neither paused nor finished.
+    pub fn is_running(&self) -> bool {
+        matches!(self.state, GameState::Running)
+    }
+
+    /// Time played, not counting pauses.
+    pub fn elapsed(&self) -> Duration {
+        match self.state {
+            GameState::Running => self.elapsed + self.start.unwrap().elapsed(),
+            _ => self.elapsed,
+        }
+    }
+
+    /// The cell at column `x` and row `y`.
+    pub fn at(&self, x: usize, y: usize) -> Cell {
+        self.grid[y][x]
+    }
+
+    /// Whether the cell at column `x` and row `y` can be changed.
+    pub fn writable(&self, x: usize, y: usize) -> bool {
+        self.at(x, y).flags & CELL_WRITABLE != 0
+    }
+
+    fn can_check(&self) -> bool {
+        self.is_running() && self.checks < self.limits.checks
+    }
+
+    fn can_hint(&self) -> bool {
+        self.is_running() && self.hints < self.limits.hints
+    }
+
+    /// Fills in the solution and ends the game.
+    pub fn complete(&mut self) {
+        if !self.is_running() {
+            return;
+        }
+
+        let before = self.grid;
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                if !self.writable(x, y) {
+                    continue;
+                }
+                let cell = &mut self.grid[y][x];
+                let solution = self.solution[y][x];
+                let is_correct = cell.value == 0 || cell.value == solution;
+                self.grid[y][x].value = solution;
+                self.grid[y][x].check(is_correct);
+            }
+        }
+
+        self.record(MoveKind::Solve, before);
+        self.elapsed = self.elapsed();
+        self.state = GameState::Solved;
+    }
+
+    /// Marks every filled cell as right or wrong, using up a check.
+    pub fn check(&mut self) {
+        if !self.can_check() {
+            return;
+        }
+
+        let mut checked = false;
+        for i in 0..SIZE {
+            for j in 0..SIZE {
+                let cell = &mut self.grid[i][j];
+                if !cell.writable() || cell.value == 0 || cell.checked() {
+                    continue;
+                }
+
+                cell.check(cell.value == self.solution[i][j]);
+                checked = true;
+            }
+        }
+
+        if checked {
+            self.checks += 1;
+        }
+    }
+
+    /// Finds the next thing the player should look at, counting it as a hint.
+    pub fn hint(&mut self) -> Option<Hint> {
+        if !self.can_hint() {
+            return None;
+        }
+        let hint = self.find_hint()?;
+        self.hints += 1;
+        Some(hint)
+    }
+
+    fn find_hint(&self) -> Option<Hint> {
+        let mut positions: Vec<(usize, usize)> = (0..SIZE)
+            .flat_map(|r| (0..SIZE).map(move |c| (r, c)))
+            .collect();
+
+        // Deductions made on top of a wrong value can't be trusted.
+        let mistake = positions.iter().find(|&&(y, x)| {
+            let cell = self.grid[y][x];
+            cell.writable() && cell.value != 0 && cell.value != self.solution[y][x]
+        });
+        if let Some(&(y, x)) = mistake {
+            return Some(Hint::Mistake((x, y)));
+        }
+
+        let values = self.grid.map(|row| row.map(|cell| cell.value));
+        let mut solver = Solver::new(&values);
+        for (y, row) in self.eliminated.iter().enumerate() {
+            for (x, &mask) in row.iter().enumerate() {
+                for value in solver::digits(mask) {
+                    solver.eliminate((x, y), value);
+                }
+            }
+        }
+        if let Some(step) = solver.next_step() {
+            return Some(Hint::Step(step));
+        }
+
+        positions.shuffle(&mut rand::thread_rng());
+        positions
+            .into_iter()
+            .find(|&(y, x)| self.grid[y][x].value == 0)
+            .map(|(y, x)| Hint::Reveal((x, y), self.solution[y][x]))
+    }
+
+    /// Applies a hint previously returned by [`Sudoku::hint`] to the board,
+    /// as a single move.
+    pub fn apply_hint(&mut self, hint: &Hint) {
+        if !self.is_running() {
+            return;
+        }
+        let before = self.grid;
+        match hint {
+            Hint::Step(step) => {
+                for &((x, y), _) in &step.placements {
+                    self.reveal(x, y);
+                }
+                for &((x, y), value) in &step.eliminations {
+                    self.eliminated[y][x] |= 1 << value;
+                    let cell = &mut self.grid[y][x];
+                    if cell.writable() && cell.value == 0 {
+                        cell.notes &= !(1 << value);
+                    }
+                }
+            }
+            Hint::Mistake((x, y)) | Hint::Reveal((x, y), _) => self.reveal(*x, *y),
+        }
+        self.record(MoveKind::Hint, before);
+    }
+
+    fn reveal(&mut self, x: usize, y: usize) {
+        if self.writable(x, y) {
+            self.grid[y][x].value = self.solution[y][x];
+            self.grid[y][x].check(true);
+        }
+    }
+
+    /// Starts the clock over, for a game built ahead of time.
+    pub fn reset_clock(&mut self) {
+        self.elapsed = Duration::ZERO;
+        if self.start.is_some() {
+            self.start = Some(Instant::now());
+        }
+    }
+
+    /// Stops the clock and hides the board.
+    pub fn pause(&mut self) {
+        if self.is_running() {
+            self.toggle_pause();
+        }
+    }
+
+    /// Pauses or resumes the game.
+    pub fn toggle_pause(&mut self) {
+        self.state = match self.state {
+            GameState::Paused => {
+                self.start = Some(Instant::now());
+                GameState::Running
+            }
+            GameState::Running => {
+                self.elapsed += self.start.unwrap().elapsed();
+                self.start = None;
+                GameState::Paused
+            }
+            _ => self.state,
+        };
+    }
+
+    /// Every move made, including the undone ones.
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    /// How many moves of [`Sudoku::history`] are currently applied.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Reverts the last applied move, returning the cell it was about.
+    /// Bookmarks placed after that move are dropped, and the one a rollback
+    /// removed comes back.
+    pub fn undo(&mut self) -> Option<(usize, usize)> {
+        if !self.is_running() || self.position == 0 {
+            return None;
+        }
+        self.position -= 1;
+        let mv = &self.history[self.position];
+        for change in mv.changes.iter().rev() {
+            self.grid[change.y][change.x] = change.old;
+        }
+        let position = self.position;
+        self.bookmarks
+            .retain(|bookmark| bookmark.position <= position);
+        self.bookmarks.extend(mv.bookmark.clone());
+        Some(mv.pos())
+    }
+
+    /// Applies the next undone move again, returning the cell it was about.
+    pub fn redo(&mut self) -> Option<(usize, usize)> {
+        if !self.is_running() || self.position == self.history.len() {
+            return None;
+        }
+        let mv = &self.history[self.position];
+        for change in &mv.changes {
+            self.grid[change.y][change.x] = change.new;
+        }
+        if let Some(bookmark) = &mv.bookmark {
+            if let Some(i) = self.bookmarks.iter().rposition(|other| other == bookmark) {
+                self.bookmarks.remove(i);
+            }
+        }
+        self.position += 1;
+        let pos = mv.pos();
+        self.check_won();
+        Some(pos)
+    }
+
+    /// Undoes or redoes moves until `position` of them are applied, as
+    /// [`Sudoku::undo`] and [`Sudoku::redo`] would.
+    pub fn jump_to(&mut self, position: usize) {
+        let position = position.min(self.history.len());
+        while self.position > position && self.undo().is_some() {}
+        while self.position < position && self.redo().is_some() {}
+    }
+
+    /// Whether conflicting digits are pointed out.
+    pub fn shows_conflicts(&self) -> bool {
+        self.show_conflicts
+    }
+
+    /// Whether conflict highlighting was used at any point of the game.
+    pub fn assisted(&self) -> bool {
+        self.assisted
+    }
+
+    /// Turns conflict highlighting on or off, marking the game as assisted.
+    pub fn toggle_conflicts(&mut self) {
+        if !self.is_running() {
+            return;
+        }
+        self.show_conflicts = !self.show_conflicts;
+        self.assisted |= self.show_conflicts;
+    }
+
+    /// Whether the value in a cell repeats in its row, column or box. This
+    /// only looks at the rules, not at the solution.
+    pub fn has_conflict(&self, x: usize, y: usize) -> bool {
+        let value = self.grid[y][x].value;
+        value != 0
+            && (0..SIZE)
+                .flat_map(|y| (0..SIZE).map(move |x| (x, y)))
+                .any(|other| {
+                    solver::sees((x, y), other) && self.grid[other.1][other.0].value == value
+                })
+    }
+
+    /// Bookmarks to roll back to, latest last.
+    pub fn bookmarks(&self) -> &[Bookmark] {
+        &self.bookmarks
+    }
+
+    /// Remembers the current board so it can be restored after a guess.
+    pub fn bookmark(&mut self) {
+        if !self.is_running() {
+            return;
+        }
+        self.bookmarks.push(Bookmark {
+            grid: self.grid,
+            position: self.position,
+        });
+    }
+
+    /// Restores the board of the latest bookmark and removes it. This is a
+    /// move of its own, so it can be undone.
+    pub fn rollback(&mut self) {
+        if !self.is_running() {
+            return;
+        }
+        let Some(bookmark) = self.bookmarks.pop() else {
+            return;
+        };
+        let before = self.grid;
+        self.grid = bookmark.grid;
+        if let Some(mv) = self.record(MoveKind::Rollback, before) {
+            mv.bookmark = Some(bookmark);
+        }
+    }
+
+    /// Records the changes made to the board since `before` as a move,
+    /// dropping any undone moves. Returns the move, unless nothing changed.
+    fn record(&mut self, kind: MoveKind, before: [[Cell; SIZE]; SIZE]) -> Option<&mut Move> {
+        let changes: Vec<_> = (0..SIZE)
+            .flat_map(|y| (0..SIZE).map(move |x| (x, y)))
+            .filter(|&(x, y)| before[y][x] != self.grid[y][x])
+            .map(|(x, y)| Change {
+                x,
+                y,
+                old: before[y][x],
+                new: self.grid[y][x],
+            })
+            .collect();
+        if changes.is_empty() {
+            return None;
+        }
+        self.history.truncate(self.position);
+        self.history.push(Move::without_bookmark(kind, changes));
+        self.position += 1;
+        self.check_won();
+        self.history.last_mut()
+    }
+
+    fn check_won(&mut self) {
+        if self.is_running() && self.is_solved() {
+            self.elapsed = self.elapsed();
+            self.state = GameState::Won;
+        }
+    }
+
+    /// Puts `value` in a writable cell, or empties it with 0, as a move.
+    pub fn update_cell(&mut self, x: usize, y: usize, value: u8) {
+        if !self.is_running() || !self.writable(x, y) {
+            return;
+        }
+        let before = self.grid;
+        self.grid[y][x].value = value;
+        self.grid[y][x].uncheck();
+        self.record(MoveKind::Edit, before);
+    }
+
+    /// Flips the candidate mark for `value` in an empty cell.
+    pub fn toggle_note(&mut self, x: usize, y: usize, value: u8) {
+        let notes = self.at(x, y).notes ^ (1 << value);
+        self.update_notes(x, y, notes);
+    }
+
+    /// Removes every note from a cell.
+    pub fn clear_notes(&mut self, x: usize, y: usize) {
+        self.update_notes(x, y, 0);
+    }
+
+    fn update_notes(&mut self, x: usize, y: usize, notes: u16) {
+        let cell = self.at(x, y);
+        if !self.is_running() || !cell.writable() || cell.value != 0 {
+            return;
+        }
+        let before = self.grid;
+        self.grid[y][x].notes = notes;
+        self.record(MoveKind::Edit, before);
+    }
+
+    /// Empties every writable cell, as one move.
+    pub fn clear_board(&mut self) {
+        if !self.is_running() {
+            return;
+        }
+        let before = self.grid;
+        for row in self.grid.iter_mut() {
+            for cell in row.iter_mut() {
+                if cell.writable() {
+                    cell.value = 0;
+                    cell.uncheck();
+                }
+            }
+        }
+        self.record(MoveKind::ClearBoard, before);
+    }
+
+    fn is_solved(&self) -> bool {
+        self.grid.iter().enumerate().all(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .all(|(x, cell)| cell.value == self.solution[y][x])
+        })
+    }
+}
+
+/// Parses the usual single-line format: 81 cells, row by row, with `.` or
+/// `0` for empty cells.
+impl FromStr for Board {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let cells: Vec<char> = s.chars().filter(|c| !c.is_whitespace()).collect();
+        if cells.len() != SIZE * SIZE {
+            bail!("expected {} cells, found {}", SIZE * SIZE, cells.len());
+        }
+
+        let mut board = Self::default();
+        for (i, &c) in cells.iter().enumerate() {
+            board.grid[i / SIZE][i % SIZE] = match c {
+                '.' | '0' => 0,
+                '1'..='9' => c as u8 - b'0',
+                _ => bail!("invalid cell '{c}'"),
+            };
+        }
+        Ok(board)
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &value in self.grid.iter().flatten() {
+            let c = if value == 0 {
+                '.'
+            } else {
+                (value + b'0') as char
+            };
+            write!(f, "{c}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Board {
+    /// The digits, row by row.
+    pub fn grid(&self) -> &[[u8; SIZE]; SIZE] {
+        &self.grid
+    }
+
+    /// A board holding `grid`, row by row, with 0 for empty cells.
+    pub fn from_grid(grid: [[u8; SIZE]; SIZE]) -> Result<Self> {
+        if let Some(&value) = grid.iter().flatten().find(|&&value| value > 9) {
+            bail!("invalid cell {value}");
+        }
+        Ok(Self { grid })
+    }
+
+    /// Whether two givens clash in a row, column or box.
+    pub fn has_conflicts(&self) -> bool {
+        let mut board = self.clone();
+        (0..SIZE).any(|row| {
+            (0..SIZE).any(|col| {
+                let value = std::mem::take(&mut board.grid[row][col]);
+                let clash = value != 0 && !board.is_valid(row, col, value);
+                board.grid[row][col] = value;
+                clash
+            })
+        })
+    }
+
+    /// Fills in the first solution found, returning whether there was one.
+    pub fn solve(&mut self) -> bool {
+        !self.has_conflicts() && self.count_solutions(1) == 1
+    }
+
+    /// Builds a random full grid.
+    pub fn generate(rng: &mut impl Rng) -> Self {
+        let mut board = Self::default();
+        board.fill_diagonals(rng);
+        board.fill_remaining(0, 0, rng);
+        board
+    }
+
+    /// Turns a full grid into a puzzle by emptying up to `num_holes` cells
+    /// in random order, skipping any whose removal would allow a second
+    /// solution.
+    pub fn generate_puzzle(&self, num_holes: usize, rng: &mut impl Rng) -> Board {
+        let mut positions: Vec<(usize, usize)> = (0..SIZE)
+            .flat_map(|r| (0..SIZE).map(move |c| (r, c)))
+            .collect();
+        positions.shuffle(rng);
+
+        let mut puzzle = self.clone();
+        for &(row, col) in positions.iter().take(num_holes) {
+            let backup = self.grid[row][col];
+            puzzle.grid[row][col] = 0;
+
+            let mut test_board = puzzle.clone();
+            if test_board.count_solutions(2) != 1 {
+                puzzle.grid[row][col] = backup;
+            }
+        }
+        puzzle
+    }
+
+    /// Whether `value` can go in the cell at `row` and `col` without
+    /// repeating a digit of its row, column or box. The cell itself is
+    /// compared too, so it should be empty.
+    pub fn is_valid(&self, row: usize, col: usize, value: u8) -> bool {
+        // Check row and column
+        for i in 0..SIZE {
+            if self.grid[row][i] == value || self.grid[i][col] == value {
+                return false;
+            }
+        }
+        // Check 3x3 subgrid
+        let start_row = row / SUBGRID_SIZE * SUBGRID_SIZE;
+        let start_col = col / SUBGRID_SIZE * SUBGRID_SIZE;
+        for i in 0..SUBGRID_SIZE {
+            for j in 0..SUBGRID_SIZE {
+                if self.grid[start_row + i][start_col + j] == value {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    fn fill_diagonals(&mut self, rng: &mut impl Rng) {
+        for i in 0..SIZE {
+            if i % SUBGRID_SIZE == 0 {
+                self.fill_box(i, i, rng);
+            }
+        }
+    }
+
+    fn fill_box(&mut self, row: usize, col: usize, rng: &mut impl Rng) {
+        let mut numbers: Vec<u8> = (1..=9).collect();
+        numbers.shuffle(rng);
+
+        for i in 0..SUBGRID_SIZE {
+            for j in 0..SUBGRID_SIZE {
+                self.grid[row + i][col + j] = numbers.pop().unwrap();
+            }
+        }
+    }
+
+    fn fill_remaining(&mut self, row: usize, col: usize, rng: &mut impl Rng) -> bool {
+        let mut numbers: Vec<u8> = (1..=9).collect();
+        numbers.shuffle(rng);
+
+        // Board filled
+        if row == SIZE - 1 && col == SIZE {
+            return true;
+        }
+
+        // Move to next row
+        if col == SIZE {
+            return self.fill_remaining(row + 1, 0, rng);
+        }
+
+        // Skip filled cells
+        if self.grid[row][col] != 0 {
+            return self.fill_remaining(row, col + 1, rng);
+        }
+
+        for &num in &numbers {
+            if self.is_valid(row, col, num) {
+                self.grid[row][col] = num;
+                // Move to next column
+                if self.fill_remaining(row, col + 1, rng) {
+                    return true;
+                }
+                self.grid[row][col] = 0;
+            }
+        }
+        false
+    }
+
+    /// Counts the solutions, stopping at `limit`. When the limit is reached
+    /// the board is left filled with the last solution found, otherwise it
+    /// is left as it was.
+    pub fn count_solutions(&mut self, limit: usize) -> usize {
+        self.count_solutions_with(&Backtracking, limit)
+    }
+
+    /// Like [`Board::count_solutions`], using another solver.
+    pub fn count_solutions_with(&mut self, solver: &dyn search::Solver, limit: usize) -> usize {
+        let mut count = 0;
+        let givens = self.grid;
+        solver.solutions(&givens, &mut |solution| {
+            count += 1;
+            if count < limit {
+                return ControlFlow::Continue(());
+            }
+            self.grid = *solution;
+            ControlFlow::Break(())
+        });
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hints_carry_eliminations_forward() {
+        let mut game = Sudoku::generate_seeded(Difficulty::Medium, 1);
+        game.set_limits(Limits {
+            checks: 0,
+            hints: u8::MAX,
+        });
+
+        let mut eliminations = 0;
+        while !game.is_solved() {
+            let hint = game.hint().expect("ran out of hints");
+            match &hint {
+                Hint::Step(step) if step.placements.is_empty() => eliminations += 1,
+                Hint::Step(_) => {}
+                _ => panic!("the solver got stuck"),
+            }
+            game.apply_hint(&hint);
+        }
+        assert!(eliminations > 0);
+    }
+
+    #[test]
+    fn generates_each_difficulty() {
+        for difficulty in Difficulty::ALL {
+            for seed in 0..3 {
+                let game = Sudoku::generate_seeded(difficulty, seed);
+                let rating = solver::rate(game.puzzle().grid());
+                assert!(
+                    rating.difficulty() == difficulty,
+                    "{} puzzle rated {} for seed {seed}",
+                    difficulty.as_str(),
+                    rating.difficulty().as_str(),
+                );
+            }
+        }
+    }
+
+    /// A new game and its first few empty cells.
+    fn game_with_empty_cells() -> (Sudoku, Vec<(usize, usize)>) {
+        let game = Sudoku::generate_seeded(Difficulty::Easy, 1);
+        let empty = (0..SIZE)
+            .flat_map(|y| (0..SIZE).map(move |x| (x, y)))
+            .filter(|&(x, y)| game.grid()[y][x].value == 0)
+            .take(3)
+            .collect();
+        (game, empty)
+    }
+
+    #[test]
+    fn undoing_a_rollback_restores_its_bookmark() {
+        let (mut game, empty) = game_with_empty_cells();
+        let (x, y) = empty[0];
+        game.bookmark();
+        game.update_cell(x, y, 5);
+        game.rollback();
+        assert!(game.bookmarks().is_empty());
+        assert_eq!(game.grid()[y][x].value, 0);
+
+        game.undo();
+        assert_eq!(game.grid()[y][x].value, 5);
+        assert_eq!(game.bookmarks().len(), 1);
+        assert_eq!(game.bookmarks()[0].position, 0);
+
+        game.redo();
+        assert_eq!(game.grid()[y][x].value, 0);
+        assert!(game.bookmarks().is_empty());
+
+        game.undo();
+        game.rollback();
+        assert_eq!(game.grid()[y][x].value, 0);
+        assert!(game.bookmarks().is_empty());
+    }
+
+    #[test]
+    fn going_back_drops_later_bookmarks() {
+        let (mut game, empty) = game_with_empty_cells();
+        game.bookmark();
+        for &(x, y) in &empty {
+            game.update_cell(x, y, 5);
+            game.bookmark();
+        }
+        let positions = |game: &Sudoku| -> Vec<usize> {
+            game.bookmarks()
+                .iter()
+                .map(|bookmark| bookmark.position)
+                .collect()
+        };
+        assert_eq!(positions(&game), [0, 1, 2, 3]);
+
+        game.undo();
+        assert_eq!(positions(&game), [0, 1, 2]);
+        game.jump_to(1);
+        assert_eq!(positions(&game), [0, 1]);
+        game.jump_to(3);
+        assert_eq!(positions(&game), [0, 1]);
+
+        // Rolling back now goes to the board after the first move.
+        game.rollback();
+        let (x, y) = empty[1];
+        assert_eq!(game.grid()[y][x].value, 0);
+        assert_eq!(game.grid()[empty[0].1][empty[0].0].value, 5);
+    }
+
+    #[test]
+    fn count_solutions_fills_the_board_at_the_limit() {
+        let puzzle = Sudoku::generate_seeded(Difficulty::Easy, 1).puzzle();
+
+        let mut board = puzzle.clone();
+        assert_eq!(board.count_solutions(2), 1);
+        assert_eq!(board, puzzle);
+
+        let mut board = puzzle.clone();
+        assert_eq!(board.count_solutions(1), 1);
+        assert!(board.grid.iter().flatten().all(|&value| value != 0));
+        assert!(!board.has_conflicts());
+    }
+}